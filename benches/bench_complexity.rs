@@ -0,0 +1,23 @@
+use binggan::{BenchRunner, Complexity, INSTRUMENTED_SYSTEM, PeakMemAlloc, black_box, plugins::*};
+
+#[global_allocator]
+pub static GLOBAL: &PeakMemAlloc<std::alloc::System> = &INSTRUMENTED_SYSTEM;
+
+fn run_bench() {
+    let mut runner = BenchRunner::new();
+    runner.add_plugin(PeakMemAllocPlugin::new(GLOBAL));
+
+    // The input is created once for each parameter
+    let mut group = runner.new_complexity_group([1_000, 10_000, 100_000], |n| {
+        (0..n as u64).rev().collect::<Vec<u64>>()
+    });
+    group.set_name("sum");
+    // Panics if the best fit is not O(n)
+    group.expect_complexity(Complexity::Linear);
+    group.register("iter sum", |data| black_box(data.iter().sum::<u64>()));
+    group.run();
+}
+
+fn main() {
+    run_bench();
+}
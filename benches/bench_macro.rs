@@ -0,0 +1,53 @@
+use std::collections::HashMap;
+
+use binggan::black_box;
+
+fn test_vec(data: &Vec<usize>) -> Vec<i32> {
+    let mut vec = Vec::new();
+    for idx in data {
+        if vec.len() <= *idx {
+            vec.resize(idx + 1, 0);
+        }
+        vec[*idx] += 1;
+    }
+    vec
+}
+fn test_hashmap(data: &Vec<usize>) -> HashMap<usize, i32> {
+    let mut map = std::collections::HashMap::new();
+    for idx in data {
+        *map.entry(*idx).or_insert(0) += 1;
+    }
+    map
+}
+
+fn make_input() -> Vec<usize> {
+    (0..100).collect()
+}
+
+// Benches with the same group are run together. The input is created just before the group runs.
+#[binggan::bench(
+    group = "max id 100; 100 ids all different",
+    input = make_input(),
+    throughput = |data: &Vec<usize>| data.len() * std::mem::size_of::<usize>()
+)]
+fn vec(data: &Vec<usize>) -> u64 {
+    // The return value of the function will be reported as the `OutputValue`
+    black_box(test_vec(data)).len() as u64
+}
+
+#[binggan::bench(
+    group = "max id 100; 100 ids all different",
+    input = make_input(),
+    throughput = |data: &Vec<usize>| data.len() * std::mem::size_of::<usize>()
+)]
+fn hashmap(data: &Vec<usize>) -> u64 {
+    black_box(test_hashmap(data)).len() as u64
+}
+
+#[binggan::bench(name = "factorial 100")]
+fn factorial() -> usize {
+    (1..=black_box(100usize)).fold(1, |acc, n| acc.wrapping_mul(n))
+}
+
+// Defines `main` and the global allocator, registers the default plugins and runs all benches.
+binggan::main!();
@@ -0,0 +1,167 @@
+//! Attribute macros for [binggan](https://docs.rs/binggan).
+//!
+//! Don't use this crate directly, enable the `macros` feature of binggan instead and use
+//! `#[binggan::bench]` together with `binggan::main!()`.
+
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::{format_ident, quote};
+use syn::{Expr, FnArg, ItemFn, LitStr, Type, parse_macro_input, spanned::Spanned};
+
+/// Declares a benchmark.
+///
+/// The function is registered and run by `binggan::main!()`.
+///
+/// # Attributes
+/// - `name = "..."`: The name of the bench. Defaults to the function name.
+/// - `group = "..."`: Benches with the same group are run together in one group.
+/// - `input = <expr>`: The input passed by reference to the function. The function needs to take
+///   exactly one argument `&T` in that case. The input is created just before the group runs and
+///   dropped after.
+/// - `throughput = <expr>`: A function `Fn(&T) -> usize` returning the size of the input in bytes.
+///   Enables throughput reporting.
+///
+/// # Example
+/// ```ignore
+/// #[binggan::bench(group = "sum", input = (0..1000).collect::<Vec<u64>>(), throughput = |data: &Vec<u64>| data.len() * 8)]
+/// fn iter_sum(data: &Vec<u64>) -> u64 {
+///     data.iter().sum()
+/// }
+///
+/// binggan::main!();
+/// ```
+#[proc_macro_attribute]
+pub fn bench(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let mut args = BenchArgs::default();
+    let parser = syn::meta::parser(|meta| {
+        if meta.path.is_ident("name") {
+            args.name = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("group") {
+            args.group = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("input") {
+            args.input = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("throughput") {
+            args.throughput = Some(meta.value()?.parse()?);
+        } else {
+            return Err(meta.error("unsupported bench attribute, expected one of `name`, `group`, `input`, `throughput`"));
+        }
+        Ok(())
+    });
+    parse_macro_input!(attr with parser);
+    let item_fn = parse_macro_input!(item as ItemFn);
+    match expand(args, item_fn) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+#[derive(Default)]
+struct BenchArgs {
+    name: Option<LitStr>,
+    group: Option<LitStr>,
+    input: Option<Expr>,
+    throughput: Option<Expr>,
+}
+
+fn expand(args: BenchArgs, item_fn: ItemFn) -> syn::Result<proc_macro2::TokenStream> {
+    let sig = &item_fn.sig;
+    if !sig.generics.params.is_empty() {
+        return Err(syn::Error::new(
+            sig.generics.span(),
+            "bench functions can't be generic",
+        ));
+    }
+    if sig.asyncness.is_some() {
+        return Err(syn::Error::new(
+            sig.span(),
+            "bench functions can't be async",
+        ));
+    }
+    let fn_name = &sig.ident;
+    let name = args
+        .name
+        .unwrap_or_else(|| LitStr::new(&fn_name.to_string(), fn_name.span()));
+    let group = match &args.group {
+        Some(group) => quote!(::core::option::Option::Some(#group)),
+        None => quote!(::core::option::Option::None),
+    };
+
+    let input_ty = match (sig.inputs.len(), &args.input) {
+        (0, None) => None,
+        (1, Some(_)) => Some(input_type(sig.inputs.first().unwrap())?),
+        (0, Some(input)) => {
+            return Err(syn::Error::new(
+                input.span(),
+                "the bench function needs an argument `&T` to receive the input",
+            ));
+        }
+        (1, None) => {
+            return Err(syn::Error::new(
+                sig.inputs.span(),
+                "benches with an argument need an `input = ...` attribute",
+            ));
+        }
+        _ => {
+            return Err(syn::Error::new(
+                sig.inputs.span(),
+                "bench functions take at most one argument",
+            ));
+        }
+    };
+    let (input_ty, make_input, call) = match (input_ty, &args.input) {
+        (Some(input_ty), Some(input)) => (quote!(#input_ty), quote!(#input), quote!(#fn_name)),
+        _ => (quote!(()), quote!(()), quote!(|_: &()| #fn_name())),
+    };
+    let input_size = match &args.throughput {
+        Some(throughput) => quote! {
+            ::core::option::Option::Some(|input: &dyn ::core::any::Any| {
+                let input = ::binggan::__private::downcast_input::<#input_ty>(input);
+                (#throughput)(input)
+            })
+        },
+        None => quote!(::core::option::Option::None),
+    };
+
+    let static_name = format_ident!(
+        "__BINGGAN_BENCH_{}",
+        fn_name.to_string().to_uppercase(),
+        span = Span::call_site()
+    );
+    Ok(quote! {
+        #item_fn
+
+        #[::binggan::__private::linkme::distributed_slice(::binggan::__private::BENCHES)]
+        #[linkme(crate = ::binggan::__private::linkme)]
+        #[doc(hidden)]
+        static #static_name: ::binggan::__private::BenchEntry = ::binggan::__private::BenchEntry {
+            name: #name,
+            group: #group,
+            file: ::core::file!(),
+            line: ::core::line!(),
+            make_input: || {
+                let input: #input_ty = #make_input;
+                ::std::boxed::Box::new(input)
+            },
+            input_size: #input_size,
+            register: |group, input| {
+                let input = ::binggan::__private::downcast_input::<#input_ty>(input);
+                group.register_with_input(#name, input, #call);
+            },
+        };
+    })
+}
+
+/// Extracts `T` from an argument of type `&T`.
+fn input_type(arg: &FnArg) -> syn::Result<Type> {
+    if let FnArg::Typed(pat_type) = arg {
+        if let Type::Reference(reference) = &*pat_type.ty {
+            if reference.mutability.is_none() && reference.lifetime.is_none() {
+                return Ok((*reference.elem).clone());
+            }
+        }
+    }
+    Err(syn::Error::new(
+        arg.span(),
+        "the input argument needs to be a shared reference `&T`",
+    ))
+}
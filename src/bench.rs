@@ -1,7 +1,9 @@
 use std::sync::atomic;
 
 use crate::{
+    NoiseFinding, RunMetadata,
     bench_id::BenchId,
+    bencher::{Bencher, BencherMode},
     black_box,
     output_value::OutputValue,
     plugins::{alloc::*, *},
@@ -12,42 +14,68 @@ use quanta::Clock;
 
 /// The trait which typically wraps a InputWithBenchmark and allows to hide the generics.
 pub trait Bench<'a> {
+    /// Returns the id of the benchmark
+    fn bench_id(&self) -> &BenchId;
     /// Returns the number of iterations the benchmark should do
     fn get_num_iter(&self) -> Option<usize>;
     fn set_num_iter(&mut self, num_iter: usize, plugins: &mut PluginManager);
     /// Sample the number of iterations the benchmark should do
     fn sample_num_iter(&mut self) -> usize;
     fn exec_bench(&mut self, plugins: &mut PluginManager);
-    fn get_results(&mut self, plugins: &mut PluginManager) -> BenchResult;
+    /// Returns the results, compared against the named baseline or the last run.
+    fn get_results(&mut self, plugins: &mut PluginManager, baseline: Option<&str>) -> BenchResult;
     fn clear_results(&mut self);
 }
 
 pub(crate) type CallBench<'a, I, O> = Box<dyn FnMut(&'a I) -> O + 'a>;
+pub(crate) type CallBencher<'a, I, O> = Box<dyn FnMut(&mut Bencher, &'a I) -> O + 'a>;
+
+/// The function of a bench. Either the whole call is measured, or the function measures itself
+/// via the [Bencher].
+pub(crate) enum BenchFn<'a, I, O> {
+    Plain(CallBench<'a, I, O>),
+    Bencher(CallBencher<'a, I, O>),
+}
 
 pub(crate) struct NamedBench<'a, I, O> {
     pub bench_id: BenchId,
-    pub fun: CallBench<'a, I, O>,
+    pub fun: BenchFn<'a, I, O>,
     pub num_group_iter: usize,
     clock: Clock,
     adjust_for_single_threaded_cpu_scheduling: bool,
+    /// The measurement overhead per sample, which is subtracted from the measured time.
+    timer_overhead_ns: f64,
 }
 impl<'a, I, O: OutputValue> NamedBench<'a, I, O> {
     pub fn new(
         bench_id: BenchId,
-        fun: CallBench<'a, I, O>,
+        fun: BenchFn<'a, I, O>,
         num_group_iter: usize,
         adjust_for_single_threaded_cpu_scheduling: bool,
+        timer_overhead_ns: f64,
     ) -> Self {
         Self {
             bench_id,
             fun,
             num_group_iter,
-            clock: Clock::new(),
+            clock: new_clock(),
             adjust_for_single_threaded_cpu_scheduling,
+            timer_overhead_ns,
         }
     }
 }
 
+#[cfg(not(miri))]
+pub(crate) fn new_clock() -> Clock {
+    Clock::new()
+}
+
+/// Miri can't execute the TSC calibration of quanta, so a mocked clock is used instead.
+#[cfg(miri)]
+pub(crate) fn new_clock() -> Clock {
+    Clock::mock().0
+}
+
 /// The result of a single benchmark.
 #[derive(Debug)]
 pub struct BenchResult {
@@ -68,9 +96,61 @@ pub struct BenchResult {
     pub output_value: Option<String>,
     /// The formatted delta between the current and previous output values, if available.
     pub output_value_delta: Option<String>,
+    /// The column title of the output value, see [OutputValue::column_title].
+    pub output_value_column_title: &'static str,
     pub(crate) serialized_output_value: Option<String>,
     /// Memory tracking is enabled and the peak memory consumption is reported.
     pub tracked_memory: bool,
+    /// Conditions of the system which may have added noise to the results.
+    /// See [Config::preflight_check](crate::Config::preflight_check).
+    pub noise_findings: Vec<NoiseFinding>,
+    /// The environment of the run.
+    pub metadata: Option<RunMetadata>,
+    /// The environment of the previous run.
+    pub old_metadata: Option<RunMetadata>,
+    /// The raw measurements of each group iteration.
+    pub raw_samples: RawSamples,
+    /// The raw measurements of the previous run, if they were stored.
+    /// See [Config::store_raw_samples](crate::Config::store_raw_samples).
+    pub old_raw_samples: Option<RawSamples>,
+}
+
+#[cfg(test)]
+impl BenchResult {
+    /// Creates a result of the bench in the group `group` without comparison or plugin data, used
+    /// in the tests of the reporters. The min and max are half and twice the median, and the
+    /// samples are the min, median and max.
+    pub(crate) fn for_test(bench_name: &str, median_ns: u64) -> Self {
+        let stats = BenchStats {
+            min_ns: median_ns / 2,
+            max_ns: median_ns * 2,
+            average_ns: median_ns,
+            median_ns,
+            avg_memory: 0,
+        };
+        BenchResult {
+            bench_id: BenchId::from_bench_name(bench_name).group_name(Some("group".to_string())),
+            stats,
+            old_stats: None,
+            perf_counter: None,
+            old_perf_counter: None,
+            input_size_in_bytes: None,
+            output_value: None,
+            output_value_delta: None,
+            output_value_column_title: "Output",
+            serialized_output_value: None,
+            tracked_memory: false,
+            noise_findings: Vec::new(),
+            metadata: None,
+            old_metadata: None,
+            raw_samples: RawSamples {
+                durations_ns: vec![stats.min_ns, stats.median_ns, stats.max_ns],
+                perf_counter: Vec::new(),
+                memory: Vec::new(),
+            },
+            old_raw_samples: None,
+        }
+    }
 }
 
 /// Bundle of input and benchmark for running benchmarks
@@ -106,6 +186,9 @@ impl<I, O: OutputValue> InputWithBenchmark<'_, I, O> {
     }
 }
 impl<'a, I, O: OutputValue> Bench<'a> for InputWithBenchmark<'a, I, O> {
+    fn bench_id(&self) -> &BenchId {
+        &self.bench.bench_id
+    }
     #[inline]
     fn sample_num_iter(&mut self) -> usize {
         self.bench.sample_and_get_iter(self.input)
@@ -124,18 +207,24 @@ impl<'a, I, O: OutputValue> Bench<'a> for InputWithBenchmark<'a, I, O> {
         self.results.push(res);
     }
 
-    fn get_results(&mut self, plugins: &mut PluginManager) -> BenchResult {
+    fn get_results(&mut self, plugins: &mut PluginManager, baseline: Option<&str>) -> BenchResult {
         let num_iter = self.get_num_iter_or_fail();
         let total_num_iter = self.bench.num_group_iter as u64 * num_iter as u64;
-        let memory_consumption: Option<&Vec<usize>> = plugins
+        let memory_consumption: Option<Vec<usize>> = plugins
             .downcast_plugin::<PeakMemAllocPlugin>(ALLOC_EVENT_LISTENER_NAME)
-            .and_then(|counters| counters.get_by_bench_id(&self.bench.bench_id));
-        let stats = compute_stats(&self.results, memory_consumption);
+            .and_then(|counters| counters.get_by_bench_id(&self.bench.bench_id))
+            .cloned();
+        let stats = compute_stats(&self.results, memory_consumption.as_ref());
         let tracked_memory = memory_consumption.is_some();
 
         let perf_counter = get_perf_counter(plugins, &self.bench.bench_id, total_num_iter);
-        let previous_run = fetch_previous_run(&self.bench.bench_id);
-        let output_value = (self.bench.fun)(self.input);
+        let raw_samples = RawSamples {
+            durations_ns: self.results.iter().map(|res| res.duration_ns).collect(),
+            perf_counter: get_perf_counter_samples(plugins, &self.bench.bench_id, num_iter as u64),
+            memory: memory_consumption.unwrap_or_default(),
+        };
+        let previous_run = fetch_previous_run(&self.bench.bench_id, baseline);
+        let output_value = self.bench.call_once(self.input);
         let output_value_delta = previous_run
             .as_ref()
             .and_then(|previous_run| previous_run.serialized_output_value.as_deref())
@@ -150,9 +239,19 @@ impl<'a, I, O: OutputValue> Bench<'a> for InputWithBenchmark<'a, I, O> {
             tracked_memory,
             output_value: output_value.format(),
             output_value_delta,
+            output_value_column_title: O::column_title(),
             serialized_output_value,
             old_stats: previous_run.as_ref().map(|previous_run| previous_run.stats),
-            old_perf_counter: previous_run.and_then(|previous_run| previous_run.perf_counter),
+            old_perf_counter: previous_run
+                .as_ref()
+                .and_then(|previous_run| previous_run.perf_counter.clone()),
+            noise_findings: Vec::new(),
+            metadata: None,
+            old_metadata: previous_run
+                .as_ref()
+                .and_then(|previous_run| previous_run.metadata.clone()),
+            raw_samples,
+            old_raw_samples: previous_run.and_then(|previous_run| previous_run.raw_samples),
         }
     }
 
@@ -182,6 +281,26 @@ fn get_perf_counter(
     }
 }
 
+/// Returns the perf counter values per iteration of each group iteration.
+fn get_perf_counter_samples(
+    _events: &mut PluginManager,
+    _bench_id: &BenchId,
+    _num_iter: u64,
+) -> Vec<PerfCounterValues> {
+    #[cfg(target_os = "linux")]
+    {
+        _events
+            .downcast_plugin::<PerfCounterPlugin>(PERF_CNT_EVENT_LISTENER_NAME)
+            .and_then(|counters| counters.get_by_bench_id_mut(_bench_id))
+            .map(|perf_cnt| perf_cnt.take_samples(_num_iter))
+            .unwrap_or_default()
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        Vec::new()
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 /// The result of a single benchmark run. This is already aggregated since a single bench may be
 /// run multiple times to improve the accuracy.
@@ -200,6 +319,42 @@ impl<O> RunResult<O> {
 }
 
 impl<'a, I, O: OutputValue> NamedBench<'a, I, O> {
+    /// Subtracts the timer overhead of `num_samples` measured samples.
+    fn subtract_timer_overhead(&self, elapsed_ns: u64, num_samples: usize) -> u64 {
+        let overhead_ns = (self.timer_overhead_ns * num_samples as f64) as u64;
+        elapsed_ns.saturating_sub(overhead_ns)
+    }
+
+    /// Calls the bench once and returns its output.
+    pub fn call_once(&mut self, input: &'a I) -> O {
+        match &mut self.fun {
+            BenchFn::Plain(fun) => fun(input),
+            BenchFn::Bencher(fun) => fun(
+                &mut Bencher::new(&self.clock, 1, BencherMode::Output),
+                input,
+            ),
+        }
+    }
+
+    /// Runs the bench `num_iter` times and returns the elapsed time in nanoseconds.
+    fn measure_ns(&mut self, input: &'a I, num_iter: usize) -> u64 {
+        match &mut self.fun {
+            BenchFn::Plain(fun) => {
+                let start = self.clock.raw();
+                for _ in 0..num_iter {
+                    #[allow(clippy::unit_arg)]
+                    black_box(fun(input));
+                }
+                self.clock.delta_as_nanos(start, self.clock.raw())
+            }
+            BenchFn::Bencher(fun) => {
+                let mut bencher = Bencher::new(&self.clock, num_iter, BencherMode::Calibrate);
+                black_box(fun(&mut bencher, input));
+                bencher.finish()
+            }
+        }
+    }
+
     #[inline]
     /// Each group has its own number of iterations. This is not the final num_iter
     pub fn sample_and_get_iter(&mut self, input: &'a I) -> usize {
@@ -208,21 +363,13 @@ impl<'a, I, O: OutputValue> NamedBench<'a, I, O> {
         const TARGET_NS_PER_BENCH: u128 = TARGET_MS_PER_BENCH as u128 * 1_000_000;
         {
             // Preliminary test if function is very slow
-            let start = self.clock.raw();
-            #[allow(clippy::unit_arg)]
-            black_box((self.fun)(input));
-            let elapsed_ms = self.clock.delta_as_nanos(start, self.clock.raw()) / 1_000_000;
+            let elapsed_ms = self.measure_ns(input, 1) / 1_000_000;
             if elapsed_ms > TARGET_MS_PER_BENCH {
                 return 1;
             }
         }
 
-        let start = self.clock.raw();
-        for _ in 0..64 {
-            #[allow(clippy::unit_arg)]
-            black_box((self.fun)(input));
-        }
-        let elapsed_ns = self.clock.delta_as_nanos(start, self.clock.raw());
+        let elapsed_ns = self.measure_ns(input, 64);
         if elapsed_ns == 0 {
             return 1;
         }
@@ -252,59 +399,73 @@ impl<'a, I, O: OutputValue> NamedBench<'a, I, O> {
         });
         debug_assert!(num_iter > 0);
 
-        // Defer dropping outputs so destructor cost is not part of the measured time.
-        let run_result = if O::defer_drop() {
-            // Accumulate raw deltas and scale once at the end.
-            // Scaling is linear, so `scale(sum(delta)) == sum(scale(delta))`.
-            let mut sum_raw = 0u64;
-            let mut adjuster = if self.adjust_for_single_threaded_cpu_scheduling {
-                SingleThreadedCpuSchedulingAdjuster::start(&self.clock)
-            } else {
-                None
-            };
-            let mut res: Option<O> = None;
-            // In this mode, we measure each iteration separately to avoid destructor cost.
-            // There may be some overhead, but it should be outweighed by benchmarks that allocate
-            for _ in 0..num_iter {
-                // We drop the value first to avoid measuring destructor time
-                // and to avoid keeping multiple outputs in memory.
-                atomic::compiler_fence(atomic::Ordering::SeqCst);
-                black_box(res.take());
-                atomic::compiler_fence(atomic::Ordering::SeqCst);
+        let run_result = match &mut self.fun {
+            // The bench measures itself, so the duration of the bencher is used instead of the
+            // clock.
+            BenchFn::Bencher(fun) => {
+                let mut bencher = Bencher::new(&self.clock, num_iter, BencherMode::Measure);
+                let output = fun(&mut bencher, input);
+                let duration_ns = bencher.finish() / num_iter as u64;
+                RunResult::new(duration_ns, output)
+            }
+            // Defer dropping outputs so destructor cost is not part of the measured time.
+            BenchFn::Plain(fun) if O::defer_drop() => {
+                // Accumulate raw deltas and scale once at the end.
+                // Scaling is linear, so `scale(sum(delta)) == sum(scale(delta))`.
+                let mut sum_raw = 0u64;
+                let mut adjuster = if self.adjust_for_single_threaded_cpu_scheduling {
+                    SingleThreadedCpuSchedulingAdjuster::start(&self.clock)
+                } else {
+                    None
+                };
+                let mut res: Option<O> = None;
+                // In this mode, we measure each iteration separately to avoid destructor cost.
+                // There may be some overhead, but it should be outweighed by benchmarks that allocate
+                for _ in 0..num_iter {
+                    // We drop the value first to avoid measuring destructor time
+                    // and to avoid keeping multiple outputs in memory.
+                    atomic::compiler_fence(atomic::Ordering::SeqCst);
+                    black_box(res.take());
+                    atomic::compiler_fence(atomic::Ordering::SeqCst);
+                    let start = self.clock.raw();
+                    atomic::compiler_fence(atomic::Ordering::SeqCst);
+                    let val = black_box(fun(input));
+                    atomic::compiler_fence(atomic::Ordering::SeqCst);
+                    let end = self.clock.raw();
+                    sum_raw = sum_raw.saturating_add(end.saturating_sub(start));
+                    res = Some(val);
+                }
+                let sum_ns = self.clock.delta_as_nanos(0, sum_raw);
+                let adjusted_ns = adjuster
+                    .as_mut()
+                    .and_then(|adjuster| adjuster.finish(sum_ns, &self.clock))
+                    .unwrap_or(sum_ns);
+                // Each iteration is a separate sample with its own timer overhead.
+                let adjusted_ns = self.subtract_timer_overhead(adjusted_ns, num_iter);
+                let duration_ns = adjusted_ns / num_iter as u64;
+                RunResult::new(duration_ns, res.unwrap())
+            }
+            BenchFn::Plain(fun) => {
                 let start = self.clock.raw();
-                atomic::compiler_fence(atomic::Ordering::SeqCst);
-                let val = black_box((self.fun)(input));
-                atomic::compiler_fence(atomic::Ordering::SeqCst);
+                let mut adjuster = if self.adjust_for_single_threaded_cpu_scheduling {
+                    SingleThreadedCpuSchedulingAdjuster::start_with_wall(start)
+                } else {
+                    None
+                };
+                let mut res: Option<O> = None;
+                for _ in 0..num_iter {
+                    res = Some(black_box(fun(input)));
+                }
                 let end = self.clock.raw();
-                sum_raw = sum_raw.saturating_add(end.saturating_sub(start));
-                res = Some(val);
-            }
-            let sum_ns = self.clock.delta_as_nanos(0, sum_raw);
-            let adjusted_ns = adjuster
-                .as_mut()
-                .and_then(|adjuster| adjuster.finish(sum_ns, &self.clock))
-                .unwrap_or(sum_ns);
-            let duration_ns = adjusted_ns / num_iter as u64;
-            RunResult::new(duration_ns, res.unwrap())
-        } else {
-            let start = self.clock.raw();
-            let mut adjuster = if self.adjust_for_single_threaded_cpu_scheduling {
-                SingleThreadedCpuSchedulingAdjuster::start_with_wall(start)
-            } else {
-                None
-            };
-            let mut res: Option<O> = None;
-            for _ in 0..num_iter {
-                res = Some(black_box((self.fun)(input)));
+                let elapsed_ns = self.clock.delta_as_nanos(start, end);
+                let adjusted_ns = adjuster
+                    .as_mut()
+                    .and_then(|adjuster| adjuster.finish_with_wall(elapsed_ns, end, &self.clock))
+                    .unwrap_or(elapsed_ns);
+                let adjusted_ns = self.subtract_timer_overhead(adjusted_ns, 1);
+                let duration_ns = adjusted_ns / num_iter as u64;
+                RunResult::new(duration_ns, res.unwrap())
             }
-            let end = self.clock.raw();
-            let elapsed_ns = self.clock.delta_as_nanos(start, end);
-            let adjusted_ns = adjuster
-                .as_mut()
-                .and_then(|adjuster| adjuster.finish_with_wall(elapsed_ns, end, &self.clock))
-                .unwrap_or(elapsed_ns);
-            let duration_ns = adjusted_ns / num_iter as u64;
-            RunResult::new(duration_ns, res.unwrap())
         };
 
         plugins.emit(PluginEvents::BenchStop {
@@ -1,7 +1,11 @@
+use std::cell::Cell;
+
 use crate::{
-    bench::{Bench, InputWithBenchmark, NamedBench},
+    bench::{Bench, BenchFn, BenchResult, InputWithBenchmark, NamedBench},
     bench_id::BenchId,
     bench_runner::BenchRunner,
+    bencher::Bencher,
+    command::{BenchCommand, UsageTotal},
     output_value::OutputValue,
 };
 
@@ -12,7 +16,7 @@ pub struct BenchGroup<'a, 'runner> {
     pub(crate) benches: Vec<Box<dyn Bench<'a> + 'a>>,
     /// The size of the input.
     /// Enables throughput reporting.
-    input_size_in_bytes: Option<usize>,
+    pub(crate) input_size_in_bytes: Option<usize>,
     pub(crate) runner: &'runner mut BenchRunner,
     pub(crate) output_value_column_title: &'static str,
 }
@@ -61,9 +65,10 @@ impl<'a, 'runner> BenchGroup<'a, 'runner> {
     {
         let bench = NamedBench::new(
             self.get_bench_id(bench_name.into()),
-            Box::new(fun),
+            BenchFn::Plain(Box::new(fun)),
             self.runner.config().get_num_iter_for_group(),
             self.runner.config.adjust_for_single_threaded_cpu_scheduling,
+            self.runner.config.timer_overhead_ns(),
         );
         self.register_named_with_input(bench, input);
     }
@@ -78,14 +83,63 @@ impl<'a, 'runner> BenchGroup<'a, 'runner> {
         let bench_name = bench_name.into();
         let bench = NamedBench::new(
             self.get_bench_id(bench_name),
-            Box::new(fun),
+            BenchFn::Plain(Box::new(fun)),
             self.runner.config().get_num_iter_for_group(),
             self.runner.config.adjust_for_single_threaded_cpu_scheduling,
+            self.runner.config.timer_overhead_ns(),
         );
 
         self.register_named_with_input(bench, &());
     }
 
+    /// Register a benchmark with the given name and function, which controls the measurement via
+    /// the passed [Bencher].
+    ///
+    /// The return value of the function will be reported as the `OutputValue`.
+    /// See [Bencher] for an example.
+    pub fn register_with_bencher<F, S: Into<String>, O: OutputValue + 'static>(
+        &mut self,
+        bench_name: S,
+        fun: F,
+    ) where
+        F: Fn(&mut Bencher) -> O + 'a,
+    {
+        self.register_with_input_and_bencher(bench_name, &(), move |bencher, _: &()| fun(bencher));
+    }
+
+    /// Register a benchmark with the given name, function and input. The function controls the
+    /// measurement via the passed [Bencher].
+    ///
+    /// The return value of the function will be reported as the `OutputValue`.
+    pub fn register_with_input_and_bencher<I, F, S: Into<String>, O: OutputValue + 'static>(
+        &mut self,
+        bench_name: S,
+        input: &'a I,
+        fun: F,
+    ) where
+        F: Fn(&mut Bencher, &'a I) -> O + 'a,
+    {
+        let bench = NamedBench::new(
+            self.get_bench_id(bench_name.into()),
+            BenchFn::Bencher(Box::new(fun)),
+            self.runner.config().get_num_iter_for_group(),
+            self.runner.config.adjust_for_single_threaded_cpu_scheduling,
+            self.runner.config.timer_overhead_ns(),
+        );
+        self.register_named_with_input(bench, input);
+    }
+
+    /// Register an external command as benchmark. The command is spawned once per iteration and
+    /// its wall time is measured, so it can be compared with Rust benches in the same group.
+    ///
+    /// The user and system CPU time and the max RSS of the command are reported as the
+    /// `OutputValue`, see [CommandUsage](crate::CommandUsage).
+    /// See [BenchCommand] for an example.
+    pub fn register_command<S: Into<String>>(&mut self, bench_name: S, command: BenchCommand) {
+        let total = Cell::new(UsageTotal::default());
+        self.register_with_bencher(bench_name, move |bencher| command.bench(bencher, &total));
+    }
+
     fn get_bench_id(&self, bench_name: String) -> BenchId {
         BenchId::from_bench_name(bench_name)
             .runner_name(self.runner.name.as_deref())
@@ -99,16 +153,8 @@ impl<'a, 'runner> BenchGroup<'a, 'runner> {
         input: &'a I,
     ) {
         self.output_value_column_title = O::column_title();
-        if let Some(filter_ast) = &self.runner.filter_ast {
-            if !crate::filter::matches_filter(filter_ast, &bench.bench_id) {
-                return;
-            }
-        } else if let Some(filter) = &self.runner.config.filter {
-            let bench_id = bench.bench_id.get_full_name();
-
-            if !bench_id.contains(filter) {
-                return;
-            }
+        if !self.runner.matches_filter(&bench.bench_id) {
+            return;
         }
 
         let bundle = InputWithBenchmark::new(
@@ -129,4 +175,13 @@ impl<'a, 'runner> BenchGroup<'a, 'runner> {
             self.output_value_column_title,
         )
     }
+
+    /// Run the benchmarks, report the results and return them.
+    pub(crate) fn run_and_get_results(&mut self) -> Vec<BenchResult> {
+        self.runner.run_group_and_get_results(
+            self.group_name.as_deref(),
+            &mut self.benches,
+            self.output_value_column_title,
+        )
+    }
 }
@@ -1,12 +1,14 @@
 use core::fmt;
 use std::fmt::{Display, Formatter};
 
+use miniserde::{Deserialize, Serialize};
+
 /// BenchId is a unique identifier for a benchmark.
 /// It has three components:
 /// - runner_name: The name of the runner that executed the benchmark.
 /// - group_name: The name of the group that the benchmark belongs to. This is typically the input name.
 /// - bench_name: The name of the benchmark.
-#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct BenchId {
     /// This is the name set on the BenchRunner.
     pub runner_name: Option<String>,
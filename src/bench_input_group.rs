@@ -1,21 +1,32 @@
+use std::marker::PhantomData;
 use std::mem;
+use std::time::Instant;
 
 use crate::output_value::OutputValue;
-use crate::plugins::{EventListener, PluginManager};
+use crate::plugins::{EventListener, PluginEvents, PluginManager};
 use crate::{
-    BenchGroup, Config, bench::NamedBench, bench_id::BenchId, bench_runner::BenchRunner, parse_args,
+    BenchGroup, Bencher, Config, bench_id::BenchId, bench_runner::BenchRunner,
+    history::print_trends, parse_args,
 };
 
 /// `InputGroup<Input, OutputValue>` is a collection of benchmarks that are run with the same inputs.
 ///
 /// It is self-contained and can be run independently.
 ///
+/// The benchmarks in a group may return different [OutputValue] types. The `OutputValue` type
+/// parameter is only kept for backwards compatibility and does not restrict [InputGroup::register].
+///
 /// The ownership of the inputs is transferred to the `InputGroup`.
 /// If this is not possible, use [BenchRunner](crate::BenchRunner) instead.
+///
+/// Large inputs can be passed as factories via [InputGroup::new_with_input_factories]. They are
+/// built just before their group runs and dropped right after.
 pub struct InputGroup<I: 'static = (), O = ()> {
     inputs: Vec<OwnedNamedInput<I>>,
-    benches_per_input: Vec<Vec<NamedBench<'static, I, O>>>,
+    benches: Vec<InputGroupBench<I>>,
+    throughput: Option<InputSize<I>>,
     runner: BenchRunner,
+    _output: PhantomData<O>,
 }
 
 impl Default for InputGroup<()> {
@@ -31,14 +42,31 @@ impl InputGroup<(), ()> {
     }
 }
 
-/// Bundles data with some name and its input_size_in_bytes.
+type InputSize<I> = Box<dyn Fn(&I) -> usize>;
+
+type RegisterWithInput<I> = Box<dyn for<'a, 'r> Fn(&mut BenchGroup<'a, 'r>, &'a I)>;
+
+/// A registered bench. It is registered on the group of each input when the group is run, so the
+/// input only needs to be borrowed for the duration of the group run. This also hides the output
+/// type, so benches with different output types can be stored together.
+struct InputGroupBench<I> {
+    name: String,
+    register: RegisterWithInput<I>,
+}
+
+/// Bundles data with some name.
 pub struct OwnedNamedInput<I> {
     pub(crate) name: String,
-    pub(crate) data: I,
-    pub(crate) input_size_in_bytes: Option<usize>,
+    pub(crate) data: InputData<I>,
+}
+
+/// The data of an input, either owned or built on demand.
+pub(crate) enum InputData<I> {
+    Owned(I),
+    Lazy(Box<dyn Fn() -> I>),
 }
 
-impl<I: 'static, O: OutputValue + 'static> InputGroup<I, O> {
+impl<I: 'static, O> InputGroup<I, O> {
     /// The inputs are a vector of tuples, where the first element is the name of the input and the
     /// second element is the input itself.
     pub fn new_with_inputs<S: Into<String>>(inputs: Vec<(S, I)>) -> Self {
@@ -50,28 +78,58 @@ impl<I: 'static, O: OutputValue + 'static> InputGroup<I, O> {
         inputs: Vec<(S, I)>,
         options: Config,
     ) -> Self {
-        use yansi::Condition;
-        yansi::whenever(Condition::TTY_AND_COLOR);
-
-        let inputs: Vec<OwnedNamedInput<I>> = inputs
+        let inputs = inputs
             .into_iter()
             .map(|(name, input)| OwnedNamedInput {
                 name: name.into(),
-                data: input,
-                input_size_in_bytes: None,
+                data: InputData::Owned(input),
             })
             .collect();
+        Self::new_with_named_inputs(inputs, options)
+    }
+
+    /// The inputs are a vector of tuples, where the first element is the name of the input and the
+    /// second element is a factory that builds the input.
+    ///
+    /// Each input is built just before its group runs and dropped right after, so only one input
+    /// is in memory at a time. The time to build each input is reported.
+    ///
+    /// # Example
+    /// ```rust
+    /// use binggan::InputGroup;
+    ///
+    /// let inputs: Vec<(&str, Box<dyn Fn() -> Vec<u64>>)> = vec![
+    ///     ("1k", Box::new(|| (0..1_000).collect())),
+    ///     ("1M", Box::new(|| (0..1_000_000).collect())),
+    /// ];
+    /// let mut group: InputGroup<Vec<u64>, u64> = InputGroup::new_with_input_factories(inputs);
+    /// group.register("sum", |data| data.iter().sum::<u64>());
+    /// ```
+    pub fn new_with_input_factories<S: Into<String>, F: Fn() -> I + 'static>(
+        inputs: Vec<(S, F)>,
+    ) -> Self {
+        let inputs = inputs
+            .into_iter()
+            .map(|(name, factory)| OwnedNamedInput {
+                name: name.into(),
+                data: InputData::Lazy(Box::new(factory)),
+            })
+            .collect();
+        Self::new_with_named_inputs(inputs, parse_args())
+    }
+
+    fn new_with_named_inputs(inputs: Vec<OwnedNamedInput<I>>, options: Config) -> Self {
+        use yansi::Condition;
+        yansi::whenever(Condition::TTY_AND_COLOR);
+
         let runner = BenchRunner::new_with_options(options);
-        let mut benches_per_input = Vec::new();
-        // Can't use resize because of clone
-        for _input in &inputs {
-            benches_per_input.push(Vec::new());
-        }
 
         InputGroup {
             inputs,
             runner,
-            benches_per_input,
+            benches: Vec::new(),
+            throughput: None,
+            _output: PhantomData,
         }
     }
 
@@ -81,54 +139,114 @@ impl<I: 'static, O: OutputValue + 'static> InputGroup<I, O> {
     where
         F: Fn(&I) -> usize + 'static,
     {
-        for input in &mut self.inputs {
-            input.input_size_in_bytes = Some(f(&input.data));
-        }
+        self.throughput = Some(Box::new(f));
     }
 
     /// Register a benchmark with the given name and function.
     ///
-    /// The return value of the function will be reported as the `OutputValue`
-    pub fn register<F, S: Into<String>>(&mut self, name: S, fun: F)
+    /// The return value of the function will be reported as the `OutputValue`.
+    /// Each benchmark may return a different `OutputValue` type.
+    ///
+    /// # Example
+    /// ```rust
+    /// use binggan::InputGroup;
+    ///
+    /// let mut group: InputGroup<Vec<u8>> = InputGroup::new_with_inputs(vec![("bytes", vec![1, 2, 3])]);
+    /// group.register("count", |data| data.len());
+    /// group.register("to_string", |data| format!("{:?}", data));
+    /// group.register("copy", |data| data.to_vec());
+    /// ```
+    pub fn register<F, S: Into<String>, R: OutputValue + 'static>(&mut self, name: S, fun: F)
     where
-        F: Fn(&I) -> O + 'static + Clone,
+        F: Fn(&I) -> R + 'static + Clone,
     {
         let name = name.into();
+        let bench_name = name.clone();
+        self.benches.push(InputGroupBench {
+            name,
+            register: Box::new(move |group, input| {
+                group.register_with_input(bench_name.clone(), input, fun.clone());
+            }),
+        });
+    }
 
-        let num_iter_for_group = self.config().get_num_iter_for_group();
-        for (ord, input) in self.inputs.iter().enumerate() {
-            let bench_id = BenchId::from_bench_name(name.clone())
-                .runner_name(self.runner.name.as_deref())
-                .group_name(Some(input.name.clone()));
-            let named_bench: NamedBench<'static, I, O> = NamedBench::new(
-                bench_id,
-                Box::new(fun.clone()),
-                num_iter_for_group,
-                self.runner.config.adjust_for_single_threaded_cpu_scheduling,
-            );
-
-            self.benches_per_input[ord].push(named_bench);
-        }
+    /// Register a benchmark with the given name and function, which controls the measurement via
+    /// the passed [Bencher].
+    ///
+    /// The return value of the function will be reported as the `OutputValue`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use binggan::{Bencher, InputGroup};
+    ///
+    /// let mut group: InputGroup<Vec<u64>> = InputGroup::new_with_inputs(vec![("1k", (0..1000).collect())]);
+    /// group.register_with_bencher("sort", |bencher: &mut Bencher, data: &Vec<u64>| {
+    ///     let mut data = data.clone();
+    ///     bencher.iter(|| data.sort());
+    /// });
+    /// ```
+    pub fn register_with_bencher<F, S: Into<String>, R: OutputValue + 'static>(
+        &mut self,
+        name: S,
+        fun: F,
+    ) where
+        F: Fn(&mut Bencher, &I) -> R + 'static + Clone,
+    {
+        let name = name.into();
+        let bench_name = name.clone();
+        self.benches.push(InputGroupBench {
+            name,
+            register: Box::new(move |group, input| {
+                group.register_with_input_and_bencher(bench_name.clone(), input, fun.clone());
+            }),
+        });
     }
 
     /// Run the benchmarks and report the results.
     pub fn run(&mut self) {
-        let mut benches_per_input = mem::take(&mut self.benches_per_input);
-        for (ord, benches) in benches_per_input.iter_mut().enumerate() {
-            let input = &self.inputs[ord];
+        let benches = mem::take(&mut self.benches);
+        for input in &self.inputs {
+            let bench_ids: Vec<BenchId> = benches
+                .iter()
+                .map(|bench| {
+                    BenchId::from_bench_name(bench.name.clone())
+                        .runner_name(self.runner.name.as_deref())
+                        .group_name(Some(input.name.clone()))
+                })
+                .filter(|bench_id| self.runner.matches_filter(bench_id))
+                .collect();
+            // Don't build inputs which are not used by any bench
+            if bench_ids.is_empty() {
+                continue;
+            }
+            // The trends are read from the history, so the input isn't needed
+            if let Some(last_n) = self.runner.config.trend {
+                print_trends(Some(&input.name), &bench_ids, last_n);
+                continue;
+            }
+            // A lazily built input is dropped at the end of this iteration, after the group.
+            let built_input;
+            let data = match &input.data {
+                InputData::Owned(data) => data,
+                InputData::Lazy(factory) => {
+                    let start = Instant::now();
+                    built_input = factory();
+                    self.runner
+                        .get_plugin_manager()
+                        .emit(PluginEvents::InputBuilt {
+                            input_name: &input.name,
+                            duration: start.elapsed().as_nanos() as u64,
+                        });
+                    &built_input
+                }
+            };
             let mut group = BenchGroup::new(&mut self.runner);
             group.set_name(&input.name);
-            // reverse so we can use pop and keep the order
-            benches.reverse();
-            while let Some(bench) = benches.pop() {
-                // The input lives in the InputGroup, so we can transmute the lifetime to 'static
-                // (probably).
-                let extended_input = unsafe { transmute_lifetime(&input.data) };
-
-                if let Some(input_size) = input.input_size_in_bytes {
-                    group.set_input_size(input_size);
-                }
-                group.register_named_with_input(bench, extended_input);
+            if let Some(input_size) = self.throughput.as_ref().map(|f| f(data)) {
+                group.set_input_size(input_size);
+            }
+            for bench in &benches {
+                (bench.register)(&mut group, data);
             }
             group.run();
         }
@@ -160,6 +278,77 @@ impl<I: 'static, O: OutputValue + 'static> InputGroup<I, O> {
     }
 }
 
-unsafe fn transmute_lifetime<I>(input: &I) -> &'static I {
-    unsafe { mem::transmute(input) }
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, rc::Rc};
+
+    use super::*;
+
+    fn config() -> Config {
+        let mut config = Config::default();
+        config.set_num_iter_for_bench(2).set_num_iter_for_group(2);
+        config.filter = None;
+        config
+    }
+
+    // The inputs are only borrowed while their group runs. Check for UB with
+    // `MIRIFLAGS=-Zmiri-disable-isolation cargo +nightly miri test input_group`
+    #[test]
+    fn input_group_runs_owned_inputs() {
+        let num_calls = Rc::new(Cell::new(0));
+        let mut group = InputGroup::<Vec<u64>>::new_with_inputs_and_options(
+            vec![("a", vec![1, 2, 3]), ("b", vec![4, 5])],
+            config(),
+        );
+        group.set_name("input_group_runs_owned_inputs");
+        group.throughput(|data| data.len() * size_of::<u64>());
+        let calls = num_calls.clone();
+        group.register("sum", move |data| {
+            calls.set(calls.get() + 1);
+            data.iter().sum::<u64>()
+        });
+        group.register("clone", |data| data.clone());
+        group.run();
+        assert!(num_calls.get() > 0);
+    }
+
+    fn lazy_inputs(num_builds: &Rc<Cell<usize>>) -> Vec<OwnedNamedInput<Vec<String>>> {
+        ["a", "b"]
+            .into_iter()
+            .map(|name| {
+                let builds = num_builds.clone();
+                OwnedNamedInput {
+                    name: name.to_string(),
+                    data: InputData::Lazy(Box::new(move || {
+                        builds.set(builds.get() + 1);
+                        vec![name.to_string(); 3]
+                    })),
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn input_group_runs_lazy_inputs() {
+        let num_builds = Rc::new(Cell::new(0));
+        let mut group =
+            InputGroup::<Vec<String>>::new_with_named_inputs(lazy_inputs(&num_builds), config());
+        group.set_name("input_group_runs_lazy_inputs");
+        group.register("concat", |data| data.concat());
+        group.run();
+        assert_eq!(num_builds.get(), 2);
+    }
+
+    #[test]
+    fn input_group_trend_builds_no_inputs() {
+        let num_builds = Rc::new(Cell::new(0));
+        let mut config = config();
+        config.set_trend(3);
+        let mut group =
+            InputGroup::<Vec<String>>::new_with_named_inputs(lazy_inputs(&num_builds), config);
+        group.set_name("input_group_trend_builds_no_inputs");
+        group.register("concat", |data| data.concat());
+        group.run();
+        assert_eq!(num_builds.get(), 0);
+    }
 }
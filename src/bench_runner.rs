@@ -1,12 +1,17 @@
 use std::cmp::Ordering;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
 
+use crate::calibration::timer_calibration;
 use crate::config::num_iter_from_env;
+use crate::history::print_trends;
 use crate::output_value::OutputValue;
-use crate::plugins::{EventListener, PluginEvents, PluginManager};
-use crate::report::PlainReporter;
+use crate::plugins::{EventListener, PERF_CNT_EVENT_LISTENER_NAME, PluginEvents, PluginManager};
+use crate::preflight::{NoiseFinding, check_system, wait_for_low_load};
+use crate::report::{JsonReporter, MarkdownReporter, OutputFormat, PlainReporter, print_info};
 use crate::{
-    BenchGroup, Config,
-    bench::{Bench, InputWithBenchmark, NamedBench},
+    BenchGroup, ComplexityGroup, Config,
+    bench::{Bench, BenchFn, BenchResult, InputWithBenchmark, NamedBench},
     bench_id::BenchId,
     black_box, parse_args,
     report::report_group,
@@ -25,10 +30,35 @@ pub struct BenchRunner {
     pub(crate) name: Option<String>,
 
     plugins: PluginManager,
+
+    /// Whether the timer calibration was printed in verbose mode.
+    printed_timer_calibration: bool,
+
+    /// Whether the first group was started, i.e. the output format reporter was added and the
+    /// system was checked.
+    first_group_done: bool,
+
+    /// The findings of the system check, set before the first group is run.
+    noise_findings: Vec<NoiseFinding>,
 }
 
 pub const EMPTY_INPUT: &() = &();
 
+/// The number of [BenchRunner]s which are not dropped yet.
+static LIVE_RUNNERS: AtomicUsize = AtomicUsize::new(0);
+
+/// Returns the number of [BenchRunner]s which are not dropped yet.
+pub(crate) fn num_live_runners() -> usize {
+    LIVE_RUNNERS.load(AtomicOrdering::SeqCst)
+}
+
+impl Drop for BenchRunner {
+    fn drop(&mut self) {
+        LIVE_RUNNERS.fetch_sub(1, AtomicOrdering::SeqCst);
+        self.plugins.emit(PluginEvents::RunStop);
+    }
+}
+
 impl Default for BenchRunner {
     fn default() -> Self {
         Self::new()
@@ -72,12 +102,19 @@ impl BenchRunner {
             .as_deref()
             .and_then(|f| tantivy_query_grammar::parse_query(f).ok());
 
+        // Calibrate the timer before any bench runs
+        timer_calibration();
+        LIVE_RUNNERS.fetch_add(1, AtomicOrdering::SeqCst);
+
         BenchRunner {
             config: options,
             filter_ast,
             input_size_in_bytes: None,
             name: None,
             plugins,
+            printed_timer_calibration: false,
+            first_group_done: false,
+            noise_findings: Vec::new(),
         }
     }
 
@@ -87,6 +124,23 @@ impl BenchRunner {
         BenchGroup::new(self)
     }
 
+    /// Creates a new [ComplexityGroup].
+    ///
+    /// The benchmarks registered on the group are run once for each parameter, with the input
+    /// created by `make_input` for that parameter. The best fitting [Complexity](crate::Complexity)
+    /// is reported for each benchmark.
+    pub fn new_complexity_group<I, P, F>(
+        &mut self,
+        params: P,
+        make_input: F,
+    ) -> ComplexityGroup<'_, I>
+    where
+        P: IntoIterator<Item = usize>,
+        F: Fn(usize) -> I,
+    {
+        ComplexityGroup::new(self, params, make_input)
+    }
+
     /// Set the name of the current test runner. This is like a header for all tests in in this
     /// runner.
     /// It is also used to distinguish when writing the results to disk.
@@ -111,9 +165,10 @@ impl BenchRunner {
         let bench_id = BenchId::from_bench_name(name).runner_name(self.name.as_deref());
         let named_bench = NamedBench::new(
             bench_id,
-            Box::new(f),
+            BenchFn::Plain(Box::new(f)),
             self.config().get_num_iter_for_group(),
             self.config.adjust_for_single_threaded_cpu_scheduling,
+            self.config.timer_overhead_ns(),
         );
         let bundle = InputWithBenchmark::new(
             EMPTY_INPUT,
@@ -126,6 +181,17 @@ impl BenchRunner {
         self
     }
 
+    /// Returns true if the bench passes the filter of the runner.
+    pub(crate) fn matches_filter(&self, bench_id: &BenchId) -> bool {
+        if let Some(filter_ast) = &self.filter_ast {
+            crate::filter::matches_filter(filter_ast, bench_id)
+        } else if let Some(filter) = &self.config.filter {
+            bench_id.get_full_name().contains(filter)
+        } else {
+            true
+        }
+    }
+
     /// Configure the benchmark.
     ///
     /// See the [Config] struct for more information.
@@ -140,8 +206,31 @@ impl BenchRunner {
         group: &mut [Box<dyn Bench<'a> + 'a>],
         output_value_column_title: &'static str,
     ) {
+        self.run_group_and_get_results(group_name, group, output_value_column_title);
+    }
+
+    /// Run the benchmarks, report the results and return them.
+    pub(crate) fn run_group_and_get_results<'a>(
+        &mut self,
+        group_name: Option<&str>,
+        group: &mut [Box<dyn Bench<'a> + 'a>],
+        output_value_column_title: &'static str,
+    ) -> Vec<BenchResult> {
         if group.is_empty() {
-            return;
+            return Vec::new();
+        }
+
+        if let Some(last_n) = self.config.trend {
+            let bench_ids: Vec<BenchId> =
+                group.iter().map(|bench| bench.bench_id().clone()).collect();
+            print_trends(group_name, &bench_ids, last_n);
+            return Vec::new();
+        }
+
+        if !self.first_group_done {
+            self.first_group_done = true;
+            self.add_output_format_reporter();
+            self.run_preflight_check();
         }
 
         self.plugins.emit(PluginEvents::GroupStart {
@@ -149,10 +238,14 @@ impl BenchRunner {
             group_name,
             output_value_column_title,
         });
+        if self.config.verbose && !self.printed_timer_calibration {
+            timer_calibration().print(self.config.subtract_timer_overhead);
+            self.printed_timer_calibration = true;
+        }
 
         const MAX_GROUP_SIZE: usize = 5;
         if self.config.verbose && group.len() > MAX_GROUP_SIZE {
-            println!(
+            print_info!(
                 "Group is quite big, splitting into chunks of {} elements",
                 MAX_GROUP_SIZE
             );
@@ -174,11 +267,13 @@ impl BenchRunner {
             }
         }
 
-        report_group(
+        let results = report_group(
             self.name.as_deref(),
             group_name,
             group,
             output_value_column_title,
+            &self.noise_findings,
+            &self.config,
             &mut self.plugins,
         );
 
@@ -186,6 +281,53 @@ impl BenchRunner {
         //for bench in group {
         //bench.clear_results();
         //}
+        results
+    }
+
+    /// Adds the reporter of the configured output format.
+    fn add_output_format_reporter(&mut self) {
+        let output_path = self.config.output_path.as_ref();
+        match self.config.output_format {
+            OutputFormat::Plain => {}
+            OutputFormat::Json => {
+                let reporter = match output_path {
+                    Some(output_path) => JsonReporter::to_file(output_path),
+                    None => JsonReporter::to_stdout(),
+                };
+                self.plugins.replace_plugin(reporter);
+            }
+            OutputFormat::Markdown => {
+                let reporter = match output_path {
+                    Some(output_path) => MarkdownReporter::to_file(output_path),
+                    None => MarkdownReporter::to_stdout(),
+                };
+                self.plugins.replace_plugin(reporter);
+            }
+        }
+    }
+
+    /// Waits for low load if configured, checks the system for noisy conditions and reports
+    /// the findings.
+    fn run_preflight_check(&mut self) {
+        let root = Path::new("/");
+        if let Some(timeout) = self.config.wait_for_low_load {
+            wait_for_low_load(root, self.config.max_load_avg, timeout);
+        }
+        let findings = if self.config.preflight_check {
+            let uses_perf_counter = self
+                .plugins
+                .get_plugins(PERF_CNT_EVENT_LISTENER_NAME)
+                .is_some();
+            check_system(root, self.config.max_load_avg, uses_perf_counter)
+        } else {
+            Vec::new()
+        };
+        if !findings.is_empty() {
+            self.plugins.emit(PluginEvents::NoiseFindings {
+                findings: &findings,
+            });
+        }
+        self.noise_findings = findings;
     }
 
     fn run_sequential<'a>(
@@ -195,13 +337,8 @@ impl BenchRunner {
     ) {
         for bench in benches {
             for iteration in 0..num_group_iter {
-                alloca::with_alloca(
-                    iteration, // we increase the byte offset by 1 for each iteration
-                    |_memory: &mut [core::mem::MaybeUninit<u8>]| {
-                        bench.exec_bench(plugins);
-                        black_box(());
-                    },
-                );
+                // we increase the byte offset by 1 for each iteration
+                Self::exec_with_stack_offset(bench, iteration, plugins);
             }
         }
     }
@@ -220,31 +357,40 @@ impl BenchRunner {
 
             for bench_idx in bench_indices.iter() {
                 let bench = &mut benches[*bench_idx];
-                // We use alloca to address memory layout randomness issues
-                // So the whole stack moves down by 1 byte for each iteration
 
                 // We loop multiple times on a single bench, since one bench could e.g. flush all the
                 // memory caches, which may or may not be like this in a real world environment.
                 // We want to capture both cases, hot loops and interleaved, to see how a bench performs under both
                 // conditions.
-                #[cfg(any(target_family = "unix", target_family = "windows"))]
-                {
-                    alloca::with_alloca(
-                        iteration, // we increase the byte offset by 1 for each iteration
-                        |_memory: &mut [core::mem::MaybeUninit<u8>]| {
-                            bench.exec_bench(plugins);
-                            black_box(());
-                        },
-                    );
-                }
-                #[cfg(not(any(target_family = "unix", target_family = "windows")))]
-                {
-                    black_box(bench.exec_bench(plugins));
-                }
+                // we increase the byte offset by 1 for each iteration
+                Self::exec_with_stack_offset(bench, iteration, plugins);
             }
         }
     }
 
+    /// We use alloca to address memory layout randomness issues
+    /// So the whole stack moves down by `offset` bytes.
+    #[inline]
+    fn exec_with_stack_offset<'a>(
+        bench: &mut Box<dyn Bench<'a> + 'a>,
+        _offset: usize,
+        plugins: &mut PluginManager,
+    ) {
+        #[cfg(all(any(target_family = "unix", target_family = "windows"), not(miri)))]
+        {
+            alloca::with_alloca(_offset, |_memory: &mut [core::mem::MaybeUninit<u8>]| {
+                bench.exec_bench(plugins);
+                black_box(());
+            });
+        }
+        // alloca is not available on all targets and not supported by miri
+        #[cfg(not(all(any(target_family = "unix", target_family = "windows"), not(miri))))]
+        {
+            bench.exec_bench(plugins);
+            black_box(());
+        }
+    }
+
     /// Detect how often each bench should be run if it is not set manually.
     fn detect_and_set_num_iter<'b>(
         benches: &mut [Box<dyn Bench<'b> + 'b>],
@@ -282,9 +428,10 @@ impl BenchRunner {
             minmax(benches.iter_mut().map(|b| b.sample_num_iter())).unwrap();
 
         if verbose {
-            println!(
+            print_info!(
                 "Estimated iters in group between {} to {}",
-                min_num_iter, max_num_iter
+                min_num_iter,
+                max_num_iter
             );
         }
         // If the difference between min and max_num_iter is more than 10x, we just set
@@ -296,7 +443,7 @@ impl BenchRunner {
             num_iter: max_num_iter,
         });
         if verbose {
-            println!("Set common iterations of {} for group", max_num_iter);
+            print_info!("Set common iterations of {} for group", max_num_iter);
         }
 
         for input_and_bench in benches {
@@ -0,0 +1,171 @@
+use std::time::Duration;
+
+use quanta::Clock;
+
+use crate::black_box;
+
+/// `Bencher` is passed to benches registered with a bencher variant, e.g.
+/// [BenchGroup::register_with_bencher](crate::BenchGroup::register_with_bencher).
+/// It gives the bench control over what is measured.
+///
+/// There are three ways to measure:
+/// - [Bencher::iter]: Runs and measures the passed closure [Bencher::num_iter] times.
+/// - [Bencher::iter_custom]: The passed closure runs the iterations itself and returns the
+///   measured duration. Useful if the duration is measured elsewhere, e.g. in a child process.
+/// - Implicit: If neither is called, the time of the whole bench closure is measured. The closure
+///   should run [Bencher::num_iter] iterations and can exclude parts, e.g. setup, with
+///   [Bencher::pause] and [Bencher::resume].
+///
+/// # Example
+/// ```rust
+/// use binggan::{black_box, BenchRunner, Bencher};
+///
+/// let mut runner = BenchRunner::new();
+/// let mut group = runner.new_group();
+/// group.register_with_bencher("sort", |bencher: &mut Bencher| {
+///     for _ in 0..bencher.num_iter() {
+///         // Don't measure the setup
+///         bencher.pause();
+///         let mut data: Vec<u64> = (0..1000).rev().collect();
+///         bencher.resume();
+///         data.sort();
+///         black_box(data);
+///     }
+/// });
+/// group.register_with_bencher("custom", |bencher: &mut Bencher| {
+///     bencher.iter_custom(|num_iter| {
+///         let start = std::time::Instant::now();
+///         for _ in 0..num_iter {
+///             black_box((0..1000u64).sum::<u64>());
+///         }
+///         start.elapsed()
+///     });
+/// });
+/// // group.run();
+/// ```
+pub struct Bencher<'a> {
+    clock: &'a Clock,
+    num_iter: usize,
+    start_raw: u64,
+    paused_raw: u64,
+    pause_start_raw: Option<u64>,
+    measured_ns: Option<u64>,
+    mode: BencherMode,
+}
+
+/// Why the bench closure is called.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum BencherMode {
+    /// Calibration runs to determine the number of iterations. They are not reported.
+    Calibrate,
+    /// The measured runs, which are reported.
+    Measure,
+    /// A single call to get the output value after the measured runs.
+    Output,
+}
+
+impl<'a> Bencher<'a> {
+    pub(crate) fn new(clock: &'a Clock, num_iter: usize, mode: BencherMode) -> Self {
+        Self {
+            clock,
+            num_iter,
+            start_raw: clock.raw(),
+            paused_raw: 0,
+            pause_start_raw: None,
+            measured_ns: None,
+            mode,
+        }
+    }
+
+    pub(crate) fn mode(&self) -> BencherMode {
+        self.mode
+    }
+
+    /// The number of iterations the bench should run.
+    ///
+    /// The reported duration is the measured duration divided by this number.
+    pub fn num_iter(&self) -> usize {
+        self.num_iter
+    }
+
+    /// Runs the closure [Bencher::num_iter] times and measures the time.
+    ///
+    /// The returned values are dropped inside the measurement.
+    pub fn iter<O, F: FnMut() -> O>(&mut self, mut f: F) {
+        let start = self.clock.raw();
+        for _ in 0..self.num_iter {
+            black_box(f());
+        }
+        let end = self.clock.raw();
+        self.add_measured(self.clock.delta_as_nanos(start, end));
+    }
+
+    /// Passes the number of iterations to the closure, which runs the iterations and returns the
+    /// measured duration for all of them.
+    pub fn iter_custom<F: FnOnce(u64) -> Duration>(&mut self, f: F) {
+        let duration = f(self.num_iter as u64);
+        self.add_measured(duration.as_nanos() as u64);
+    }
+
+    /// Pauses the implicit time measurement, until [Bencher::resume] is called.
+    ///
+    /// This only applies if neither [Bencher::iter] nor [Bencher::iter_custom] is called.
+    #[inline]
+    pub fn pause(&mut self) {
+        if self.pause_start_raw.is_none() {
+            self.pause_start_raw = Some(self.clock.raw());
+        }
+    }
+
+    /// Resumes the implicit time measurement after [Bencher::pause].
+    #[inline]
+    pub fn resume(&mut self) {
+        if let Some(pause_start_raw) = self.pause_start_raw.take() {
+            let paused = self.clock.raw().saturating_sub(pause_start_raw);
+            self.paused_raw = self.paused_raw.saturating_add(paused);
+        }
+    }
+
+    fn add_measured(&mut self, ns: u64) {
+        self.measured_ns = Some(self.measured_ns.unwrap_or(0).saturating_add(ns));
+    }
+
+    /// Returns the measured duration in nanoseconds for all iterations.
+    pub(crate) fn finish(mut self) -> u64 {
+        self.resume();
+        if let Some(measured_ns) = self.measured_ns {
+            return measured_ns;
+        }
+        let end_raw = self.clock.raw();
+        let elapsed_raw = end_raw
+            .saturating_sub(self.start_raw)
+            .saturating_sub(self.paused_raw);
+        self.clock.delta_as_nanos(0, elapsed_raw)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn iter_custom_uses_returned_duration() {
+        let clock = Clock::new();
+        let mut bencher = Bencher::new(&clock, 10, BencherMode::Measure);
+        bencher.iter_custom(|num_iter| {
+            assert_eq!(num_iter, 10);
+            Duration::from_nanos(1_000)
+        });
+        assert_eq!(bencher.finish(), 1_000);
+    }
+
+    #[test]
+    fn pause_excludes_time() {
+        let clock = Clock::new();
+        let mut bencher = Bencher::new(&clock, 1, BencherMode::Measure);
+        bencher.pause();
+        std::thread::sleep(Duration::from_millis(20));
+        bencher.resume();
+        assert!(bencher.finish() < 10_000_000);
+    }
+}
@@ -0,0 +1,7 @@
+//! Compares saved binggan results without running the benches.
+//!
+//! `cargo run --features cli --bin binggan -- [OPTIONS] <OLD> <NEW>`
+
+fn main() {
+    binggan::__cli::run_cli();
+}
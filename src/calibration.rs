@@ -0,0 +1,117 @@
+use std::sync::{OnceLock, atomic};
+
+use quanta::Clock;
+
+use crate::bench::new_clock;
+use crate::black_box;
+use crate::report::format::format_float;
+use crate::report::print_info;
+
+/// The measured resolution and overhead of the timer, which is used to measure the benches.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct TimerCalibration {
+    /// The smallest non-zero difference between two timer reads in nanoseconds.
+    pub resolution_ns: f64,
+    /// The overhead of measuring a single sample in nanoseconds, i.e. two timer reads plus
+    /// compiler fences. This is the time measured for an empty closure.
+    pub overhead_ns: f64,
+}
+
+/// Returns the timer calibration. It is measured once per process.
+pub(crate) fn timer_calibration() -> TimerCalibration {
+    static CALIBRATION: OnceLock<TimerCalibration> = OnceLock::new();
+    *CALIBRATION.get_or_init(|| calibrate(&new_clock()))
+}
+
+#[cfg(not(miri))]
+const NUM_ROUNDS: usize = 200;
+#[cfg(miri)]
+const NUM_ROUNDS: usize = 3;
+const SAMPLES_PER_ROUND: usize = 100;
+
+fn calibrate(clock: &Clock) -> TimerCalibration {
+    let mut resolution_raw = u64::MAX;
+    for _ in 0..1_000 {
+        let start = clock.raw();
+        let mut end = clock.raw();
+        // Spin until the timer advances, bounded in case the timer is mocked
+        for _ in 0..1_000 {
+            if end != start {
+                break;
+            }
+            end = clock.raw();
+        }
+        if end > start {
+            resolution_raw = resolution_raw.min(end - start);
+        }
+    }
+    let resolution_ns = if resolution_raw == u64::MAX {
+        0.0
+    } else {
+        raw_to_ns(clock, resolution_raw)
+    };
+
+    // Measure an empty closure the same way as a sample in `NamedBench::exec_bench`.
+    let empty = || {};
+    let mut per_sample_raw: Vec<f64> = (0..NUM_ROUNDS)
+        .map(|_| {
+            let mut sum_raw = 0u64;
+            for _ in 0..SAMPLES_PER_ROUND {
+                atomic::compiler_fence(atomic::Ordering::SeqCst);
+                let start = clock.raw();
+                atomic::compiler_fence(atomic::Ordering::SeqCst);
+                #[allow(clippy::unit_arg)]
+                black_box(empty());
+                atomic::compiler_fence(atomic::Ordering::SeqCst);
+                let end = clock.raw();
+                sum_raw = sum_raw.saturating_add(end.saturating_sub(start));
+            }
+            sum_raw as f64 / SAMPLES_PER_ROUND as f64
+        })
+        .collect();
+    per_sample_raw.sort_by(|a, b| a.total_cmp(b));
+    // The median is robust against rounds that were interrupted
+    let median_raw = per_sample_raw[per_sample_raw.len() / 2];
+    let overhead_ns = raw_to_ns(clock, (median_raw * 1_000.0) as u64) / 1_000.0;
+
+    TimerCalibration {
+        resolution_ns,
+        overhead_ns,
+    }
+}
+
+/// Converts raw timer ticks to nanoseconds with sub-nanosecond precision.
+fn raw_to_ns(clock: &Clock, raw: u64) -> f64 {
+    if raw == 0 {
+        return 0.0;
+    }
+    // `delta_as_nanos` truncates to full nanoseconds, so scale up first.
+    let scale = 1_000u64.min(u64::MAX / raw);
+    clock.delta_as_nanos(0, raw * scale) as f64 / scale as f64
+}
+
+impl TimerCalibration {
+    /// Prints the calibration, used in verbose mode.
+    pub(crate) fn print(&self, subtracted: bool) {
+        print_info!(
+            "Timer resolution: {}ns, overhead per sample: {}ns{}",
+            format_float(self.resolution_ns),
+            format_float(self.overhead_ns),
+            if subtracted { " (subtracted)" } else { "" }
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn calibration_is_plausible() {
+        let calibration = timer_calibration();
+        assert!(calibration.overhead_ns >= 0.0);
+        // Two timer reads should not take longer than 10µs
+        assert!(calibration.overhead_ns < 10_000.0);
+        assert!(calibration.resolution_ns < 10_000.0);
+    }
+}
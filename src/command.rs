@@ -0,0 +1,326 @@
+use std::cell::Cell;
+use std::ffi::OsString;
+use std::fs::File;
+use std::path::PathBuf;
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::time::{Duration, Instant};
+
+use miniserde::{Deserialize, Serialize};
+
+use crate::bencher::{Bencher, BencherMode};
+use crate::output_value::{OutputValue, format_percentage_delta};
+use crate::report::format::{bytes_to_string, format_duration};
+
+/// An external command, which is benchmarked via
+/// [BenchGroup::register_command](crate::BenchGroup::register_command).
+///
+/// The command is spawned once per iteration. Its stdout and stderr are discarded and the stdin
+/// is empty, unless a file is set via [BenchCommand::stdin_file].
+/// The bench panics if the command can't be spawned or exits with a non-zero status.
+///
+/// # Example
+/// ```rust
+/// use binggan::{BenchCommand, BenchRunner};
+///
+/// let mut runner = BenchRunner::new();
+/// let mut group = runner.new_group();
+/// group.register_command(
+///     "gzip",
+///     BenchCommand::new("gzip").args(["-c", "-9"]).env("LC_ALL", "C").stdin_file("Cargo.toml"),
+/// );
+/// // group.run();
+/// ```
+#[derive(Debug, Clone)]
+pub struct BenchCommand {
+    program: OsString,
+    args: Vec<OsString>,
+    envs: Vec<(OsString, OsString)>,
+    stdin_file: Option<PathBuf>,
+}
+
+impl BenchCommand {
+    /// Creates a command for the given program. The program is resolved via `PATH` like
+    /// [std::process::Command].
+    pub fn new<S: Into<OsString>>(program: S) -> Self {
+        Self {
+            program: program.into(),
+            args: Vec::new(),
+            envs: Vec::new(),
+            stdin_file: None,
+        }
+    }
+
+    /// Adds an argument.
+    pub fn arg<S: Into<OsString>>(mut self, arg: S) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    /// Adds multiple arguments.
+    pub fn args<I: IntoIterator<Item = S>, S: Into<OsString>>(mut self, args: I) -> Self {
+        self.args.extend(args.into_iter().map(Into::into));
+        self
+    }
+
+    /// Sets an environment variable for the command. The environment of the bench process is
+    /// inherited.
+    pub fn env<K: Into<OsString>, V: Into<OsString>>(mut self, key: K, value: V) -> Self {
+        self.envs.push((key.into(), value.into()));
+        self
+    }
+
+    /// Passes the content of the file as stdin to the command. The file is opened for each
+    /// iteration.
+    pub fn stdin_file<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.stdin_file = Some(path.into());
+        self
+    }
+
+    fn display_name(&self) -> String {
+        let mut name = self.program.to_string_lossy().to_string();
+        for arg in &self.args {
+            name.push(' ');
+            name.push_str(&arg.to_string_lossy());
+        }
+        name
+    }
+
+    fn spawn(&self) -> Child {
+        let stdin = match &self.stdin_file {
+            Some(path) => Stdio::from(File::open(path).unwrap_or_else(|err| {
+                panic!("binggan: could not open stdin file {:?}: {}", path, err)
+            })),
+            None => Stdio::null(),
+        };
+        Command::new(&self.program)
+            .args(&self.args)
+            .envs(self.envs.iter().map(|(key, value)| (key, value)))
+            .stdin(stdin)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .unwrap_or_else(|err| {
+                panic!(
+                    "binggan: could not spawn `{}`: {}",
+                    self.display_name(),
+                    err
+                )
+            })
+    }
+
+    /// Runs the command once and returns the wall time and the resource usage of the child.
+    fn run_once(&self) -> (Duration, CommandUsage) {
+        let start = Instant::now();
+        let child = self.spawn();
+        let (status, usage) = wait_with_usage(child);
+        let elapsed = start.elapsed();
+        assert!(
+            status.success(),
+            "binggan: `{}` failed with {}",
+            self.display_name(),
+            status
+        );
+        (elapsed, usage)
+    }
+
+    /// Runs the command [Bencher::num_iter] times. The wall time is reported as the duration of
+    /// the bench.
+    ///
+    /// The resource usage of the measured runs is accumulated in `total`. The output value is the
+    /// average over them, so the command is not spawned again to get the output value.
+    pub(crate) fn bench(&self, bencher: &mut Bencher, total: &Cell<UsageTotal>) -> CommandUsage {
+        let mode = bencher.mode();
+        if mode == BencherMode::Output {
+            return total.take().average();
+        }
+        bencher.iter_custom(|num_iter| {
+            let mut wall = Duration::ZERO;
+            let mut run_total = total.get();
+            for _ in 0..num_iter {
+                let (elapsed, usage) = self.run_once();
+                wall += elapsed;
+                run_total.add(usage);
+            }
+            // Calibration runs only determine the number of iterations and are not reported.
+            if mode == BencherMode::Measure {
+                total.set(run_total);
+            }
+            wall
+        });
+        <CommandUsage as Default>::default()
+    }
+}
+
+/// The accumulated resource usage of the measured runs of a command.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct UsageTotal {
+    user_ns: u64,
+    sys_ns: u64,
+    max_rss_bytes: u64,
+    num_iter: u64,
+}
+
+impl UsageTotal {
+    fn add(&mut self, usage: CommandUsage) {
+        self.user_ns += usage.user_ns;
+        self.sys_ns += usage.sys_ns;
+        self.max_rss_bytes = self.max_rss_bytes.max(usage.max_rss_bytes);
+        self.num_iter += 1;
+    }
+
+    fn average(&self) -> CommandUsage {
+        let num_iter = self.num_iter.max(1);
+        CommandUsage {
+            user_ns: self.user_ns / num_iter,
+            sys_ns: self.sys_ns / num_iter,
+            max_rss_bytes: self.max_rss_bytes,
+        }
+    }
+}
+
+/// The resource usage of a benchmarked command, reported as the output value of
+/// [BenchGroup::register_command](crate::BenchGroup::register_command).
+///
+/// The CPU times are averaged over all measured iterations of the group, the max RSS is the
+/// maximum of them.
+/// The resource usage is only collected on Linux, via `wait4`. It is zero on other platforms.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommandUsage {
+    /// The user CPU time of the command in nanoseconds.
+    pub user_ns: u64,
+    /// The system CPU time of the command in nanoseconds.
+    pub sys_ns: u64,
+    /// The maximum resident set size of the command in bytes.
+    pub max_rss_bytes: u64,
+}
+
+impl OutputValue for CommandUsage {
+    fn format(&self) -> Option<String> {
+        Some(format!(
+            "user {} sys {} rss {}",
+            format_duration(self.user_ns),
+            format_duration(self.sys_ns),
+            bytes_to_string(self.max_rss_bytes)
+        ))
+    }
+
+    fn column_title() -> &'static str {
+        "Command"
+    }
+
+    fn serialize(&self) -> Option<String> {
+        Some(miniserde::json::to_string(self))
+    }
+
+    fn deserialize(serialized: &str) -> Option<Self> {
+        miniserde::json::from_str(serialized).ok()
+    }
+
+    /// The delta of the CPU time (user + sys).
+    fn format_delta(&self, old: &Self) -> Option<String> {
+        format_percentage_delta(
+            (self.user_ns + self.sys_ns) as f64,
+            (old.user_ns + old.sys_ns) as f64,
+        )
+    }
+}
+
+/// Waits for the child and collects its resource usage via `wait4`.
+#[cfg(target_os = "linux")]
+fn wait_with_usage(child: Child) -> (ExitStatus, CommandUsage) {
+    use std::os::unix::process::ExitStatusExt;
+
+    let pid = child.id() as libc::pid_t;
+    let mut status: libc::c_int = 0;
+    // SAFETY: rusage is a plain C struct, for which all zero bytes is a valid value.
+    let mut rusage: libc::rusage = unsafe { std::mem::zeroed() };
+    loop {
+        // SAFETY: The pointers are valid for the duration of the call. The child was spawned by
+        // us and is not waited on anywhere else, so the pid can't be reused before it's reaped.
+        let ret = unsafe { libc::wait4(pid, &mut status, 0, &mut rusage) };
+        if ret == pid {
+            break;
+        }
+        let err = std::io::Error::last_os_error();
+        if err.kind() != std::io::ErrorKind::Interrupted {
+            panic!("binggan: wait4 failed: {}", err);
+        }
+    }
+    let to_ns =
+        |time: libc::timeval| time.tv_sec as u64 * 1_000_000_000 + time.tv_usec as u64 * 1_000;
+    let usage = CommandUsage {
+        user_ns: to_ns(rusage.ru_utime),
+        sys_ns: to_ns(rusage.ru_stime),
+        // ru_maxrss is in kilobytes on Linux
+        max_rss_bytes: rusage.ru_maxrss as u64 * 1024,
+    };
+    (ExitStatus::from_raw(status), usage)
+}
+
+/// Waits for the child. The resource usage is not available on this platform.
+#[cfg(not(target_os = "linux"))]
+fn wait_with_usage(mut child: Child) -> (ExitStatus, CommandUsage) {
+    let status = child
+        .wait()
+        .unwrap_or_else(|err| panic!("binggan: waiting for command failed: {}", err));
+    (status, <CommandUsage as Default>::default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn command_reports_usage() {
+        let command = BenchCommand::new("sh")
+            .args(["-c", "test \"$BINGGAN_TEST\" = 1 && cat > /dev/null"])
+            .env("BINGGAN_TEST", "1")
+            .stdin_file("Cargo.toml");
+        let (elapsed, usage) = command.run_once();
+        assert!(elapsed > Duration::ZERO);
+        assert!(usage.max_rss_bytes > 0);
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    #[should_panic(expected = "failed with")]
+    fn command_panics_on_failure() {
+        BenchCommand::new("sh").args(["-c", "exit 3"]).run_once();
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn usage_is_averaged_over_measured_runs() {
+        let clock = quanta::Clock::new();
+        let total = Cell::new(UsageTotal::default());
+        let command = BenchCommand::new("true");
+        command.bench(&mut Bencher::new(&clock, 3, BencherMode::Calibrate), &total);
+        assert_eq!(total.get().num_iter, 0);
+        command.bench(&mut Bencher::new(&clock, 2, BencherMode::Measure), &total);
+        command.bench(&mut Bencher::new(&clock, 2, BencherMode::Measure), &total);
+        assert_eq!(total.get().num_iter, 4);
+
+        // Getting the output value doesn't run the command again.
+        let failing = BenchCommand::new("false");
+        let usage = failing.bench(&mut Bencher::new(&clock, 1, BencherMode::Output), &total);
+        assert!(usage.max_rss_bytes > 0);
+        assert_eq!(total.get().num_iter, 0);
+    }
+
+    #[test]
+    fn command_usage_roundtrip_and_delta() {
+        let usage = CommandUsage {
+            user_ns: 150,
+            sys_ns: 0,
+            max_rss_bytes: 1024,
+        };
+        let serialized = usage.serialize().unwrap();
+        assert_eq!(CommandUsage::deserialize(&serialized), Some(usage));
+        let old = CommandUsage {
+            user_ns: 100,
+            ..usage
+        };
+        assert_eq!(usage.format_delta(&old), Some("(+50.00%)".to_string()));
+    }
+}
@@ -0,0 +1,373 @@
+//! Offline comparison of saved results, used by the `binggan` binary.
+//!
+//! Compares two results directories, e.g. the last run in `target/binggan` and a named baseline
+//! in `target/binggan/baselines/<name>`, without running the benches again. With `--merge` the
+//! results of repeated runs are merged into a baseline instead, see [crate::merge].
+
+use std::cmp::Ordering;
+use std::path::{Path, PathBuf};
+
+use rustop::opts;
+use tantivy_query_grammar::UserInputAst;
+
+use crate::{
+    bench_id::BenchId,
+    filter::matches_filter,
+    merge::{MergeStat, merge_results},
+    report::{format::format_duration, print_table},
+    stats::{BenchStats, compute_diff, compute_percentage_diff},
+    write_results::{
+        SavedResult, get_baseline_directory, read_results_in_directory, write_baseline,
+    },
+};
+
+/// The comparison of a bench between the old and the new results.
+#[derive(Debug, Clone)]
+struct Comparison {
+    bench_id: BenchId,
+    /// The file name of the result, which is namespaced by the bench binary.
+    file_name: String,
+    /// Whether other comparisons have the same bench id, i.e. of other bench binaries.
+    ambiguous: bool,
+    old: Option<BenchStats>,
+    new: Option<BenchStats>,
+}
+
+impl Comparison {
+    /// The change of the median in percent, if the bench exists in both results.
+    fn median_change_pct(&self) -> Option<f64> {
+        match (self.old, self.new) {
+            (Some(old), Some(new)) if old.median_ns != 0 => Some(compute_percentage_diff(
+                new.median_ns as f64,
+                old.median_ns as f64,
+            )),
+            _ => None,
+        }
+    }
+
+    fn to_columns(&self) -> Vec<String> {
+        let duration = |stats: Option<BenchStats>, f: fn(&BenchStats) -> u64| {
+            stats
+                .map(|stats| format_duration(f(&stats)))
+                .unwrap_or_else(|| "-".to_string())
+        };
+        let change = match (&self.old, &self.new) {
+            (Some(_), Some(new)) => compute_diff(new, None, self.old, |stats| stats.median_ns),
+            (None, Some(_)) => "new".to_string(),
+            (Some(_), None) => "removed".to_string(),
+            (None, None) => String::new(),
+        };
+        let mut name = display_name(&self.bench_id);
+        if self.ambiguous {
+            // The file name starts with the escaped name of the bench binary
+            let binary_name = self.file_name.split('.').next().unwrap_or_default();
+            name.push_str(&format!(" [{}]", binary_name));
+        }
+        vec![
+            name,
+            format!("Avg: {}", duration(self.old, |stats| stats.average_ns)),
+            format!("-> {}", duration(self.new, |stats| stats.average_ns)),
+            format!("Median: {}", duration(self.old, |stats| stats.median_ns)),
+            format!("-> {}", duration(self.new, |stats| stats.median_ns)),
+            change,
+        ]
+    }
+}
+
+/// The name parts of the bench id joined by `/`.
+fn display_name(bench_id: &BenchId) -> String {
+    [
+        bench_id.runner_name.as_deref(),
+        bench_id.group_name.as_deref(),
+        Some(bench_id.bench_name.as_str()),
+    ]
+    .into_iter()
+    .flatten()
+    .filter(|part| !part.is_empty())
+    .collect::<Vec<_>>()
+    .join("/")
+}
+
+/// Matches the results by file name, which is unique per bench binary and bench id. Benches
+/// which are filtered out or changed less than `threshold_pct` are removed. The comparisons are
+/// sorted by name, or by the largest change of the median if `sort_by_change` is set.
+fn compare(
+    old: Vec<SavedResult>,
+    new: Vec<SavedResult>,
+    filter: Option<&UserInputAst>,
+    threshold_pct: Option<f64>,
+    sort_by_change: bool,
+) -> Vec<Comparison> {
+    let mut comparisons: Vec<Comparison> = new
+        .into_iter()
+        .map(|result| Comparison {
+            bench_id: result.bench_id,
+            file_name: result.file_name,
+            ambiguous: false,
+            old: None,
+            new: Some(result.file.stats),
+        })
+        .collect();
+    let mut unmatched = Vec::new();
+    for result in old {
+        match comparisons
+            .iter_mut()
+            .find(|comparison| comparison.file_name == result.file_name)
+        {
+            Some(comparison) => comparison.old = Some(result.file.stats),
+            None => unmatched.push(result),
+        }
+    }
+    // Files of older versions of binggan have other file names. They are matched by bench id,
+    // if only one result of the other directory has it.
+    for result in unmatched {
+        let mut candidates = comparisons.iter_mut().filter(|comparison| {
+            comparison.old.is_none() && comparison.bench_id == result.bench_id
+        });
+        match (candidates.next(), candidates.next()) {
+            (Some(comparison), None) => comparison.old = Some(result.file.stats),
+            _ => comparisons.push(Comparison {
+                bench_id: result.bench_id,
+                file_name: result.file_name,
+                ambiguous: false,
+                old: Some(result.file.stats),
+                new: None,
+            }),
+        }
+    }
+    comparisons.retain(|comparison| {
+        let matches = filter.is_none_or(|filter| matches_filter(filter, &comparison.bench_id));
+        let above_threshold = match (threshold_pct, comparison.median_change_pct()) {
+            (Some(threshold_pct), Some(change_pct)) => change_pct.abs() >= threshold_pct,
+            _ => true,
+        };
+        matches && above_threshold
+    });
+    comparisons.sort_by(|a, b| (&a.bench_id, &a.file_name).cmp(&(&b.bench_id, &b.file_name)));
+    let bench_ids: Vec<BenchId> = comparisons
+        .iter()
+        .map(|comparison| comparison.bench_id.clone())
+        .collect();
+    for comparison in &mut comparisons {
+        let count = bench_ids
+            .iter()
+            .filter(|bench_id| **bench_id == comparison.bench_id)
+            .count();
+        comparison.ambiguous = count > 1;
+    }
+    if sort_by_change {
+        // Benches without a change, i.e. new or removed ones, are listed last
+        comparisons.sort_by(|a, b| {
+            let a = a.median_change_pct().map(f64::abs);
+            let b = b.median_change_pct().map(f64::abs);
+            match (a, b) {
+                (Some(a), Some(b)) => b.total_cmp(&a),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            }
+        });
+    }
+    comparisons
+}
+
+/// Returns the directory if `name_or_path` is an existing directory, otherwise the directory of
+/// the named baseline.
+fn resolve_results_directory(name_or_path: &str) -> PathBuf {
+    let path = Path::new(name_or_path);
+    if path.is_dir() {
+        path.to_path_buf()
+    } else {
+        get_baseline_directory(name_or_path)
+    }
+}
+
+/// The entry point of the `binggan` binary.
+pub fn run_cli() {
+    yansi::whenever(yansi::Condition::TTY_AND_COLOR);
+    let (args, _rest) = opts! {
+        synopsis "Compares saved binggan results without running the benches.\n\
+                  DIRS are results directories, e.g. target/binggan for the last run, \
+                  or names of baselines in target/binggan/baselines. \
+                  Without --merge, exactly two are expected: the old and the new results.";
+        opt filter:Option<String>, desc:"Show only benches matching the filter. Supports AND/OR and fields like runner_name, group_name, bench_name.";
+        opt sort:bool, desc:"Sort by the largest change of the median.";
+        opt threshold:Option<f64>, desc:"Hide benches whose median changed less than the given percentage.";
+        opt merge:Option<String>, desc:"Merge the results of all DIRS, e.g. of repeated runs, into the named baseline.";
+        opt merge_stat:String=String::from("median"), desc:"How the stats are merged if not all runs stored raw samples: median or min.";
+        param dirs:Vec<String>, desc:"The results directories or baseline names.";
+    }
+    .parse_or_exit();
+
+    let filter = args.filter.as_deref().map(|filter| {
+        tantivy_query_grammar::parse_query(filter).unwrap_or_else(|_| {
+            eprintln!("Invalid filter: {}", filter);
+            std::process::exit(2);
+        })
+    });
+    let read = |name_or_path: &str| {
+        let directory = resolve_results_directory(name_or_path);
+        read_results_in_directory(&directory).unwrap_or_else(|err| {
+            eprintln!("Could not read results in {}: {}", directory.display(), err);
+            std::process::exit(2);
+        })
+    };
+    if let Some(baseline) = &args.merge {
+        let stat: MergeStat = args.merge_stat.parse().unwrap_or_else(|err| {
+            eprintln!("{}", err);
+            std::process::exit(2);
+        });
+        let runs: Vec<Vec<SavedResult>> = args
+            .dirs
+            .iter()
+            .map(|name_or_path| {
+                let mut results = read(name_or_path);
+                results.retain(|result| {
+                    filter
+                        .as_ref()
+                        .is_none_or(|filter| matches_filter(filter, &result.bench_id))
+                });
+                results
+            })
+            .collect();
+        let mut rows = Vec::new();
+        for merged in merge_results(runs, stat) {
+            if let Err(err) = write_baseline(&merged.file_name, baseline, &merged.file) {
+                eprintln!("Could not write {}: {}", merged.bench_id, err);
+                std::process::exit(1);
+            }
+            let stats = merged.file.stats;
+            rows.push(vec![
+                display_name(&merged.bench_id),
+                format!("Runs: {}", merged.num_runs),
+                format!("Avg: {}", format_duration(stats.average_ns)),
+                format!("Median: {}", format_duration(stats.median_ns)),
+                if merged.pooled {
+                    "pooled samples".to_string()
+                } else {
+                    format!("{:?} of stats", stat).to_lowercase()
+                },
+            ]);
+        }
+        print_table(&rows);
+        println!(
+            "Merged {} runs into baseline {}",
+            args.dirs.len(),
+            get_baseline_directory(baseline).display()
+        );
+        return;
+    }
+
+    let [old, new] = args.dirs.as_slice() else {
+        eprintln!(
+            "Expected two results directories to compare, got {}",
+            args.dirs.len()
+        );
+        std::process::exit(2);
+    };
+    let comparisons = compare(
+        read(old),
+        read(new),
+        filter.as_ref(),
+        args.threshold,
+        args.sort,
+    );
+    let rows: Vec<Vec<String>> = comparisons
+        .iter()
+        .map(|comparison| comparison.to_columns())
+        .collect();
+    print_table(&rows);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn saved(bench_name: &str, median_ns: u64) -> SavedResult {
+        let bench_id = BenchId::from_bench_name(bench_name).group_name(Some("group".to_string()));
+        let stats = BenchStats {
+            min_ns: median_ns,
+            max_ns: median_ns,
+            average_ns: median_ns,
+            median_ns,
+            avg_memory: 0,
+        };
+        SavedResult::for_test(bench_id, stats)
+    }
+
+    fn names(comparisons: &[Comparison]) -> Vec<&str> {
+        comparisons
+            .iter()
+            .map(|comparison| comparison.bench_id.bench_name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn compare_test() {
+        let old = || {
+            vec![
+                saved("a", 100),
+                saved("b", 100),
+                saved("c", 100),
+                saved("old", 1),
+            ]
+        };
+        let new = || {
+            vec![
+                saved("a", 101),
+                saved("b", 150),
+                saved("c", 80),
+                saved("new", 1),
+            ]
+        };
+
+        let comparisons = compare(old(), new(), None, None, false);
+        assert_eq!(names(&comparisons), vec!["a", "b", "c", "new", "old"]);
+        assert_eq!(comparisons[1].median_change_pct(), Some(50.0));
+        assert_eq!(comparisons[3].to_columns()[5], "new");
+        assert_eq!(comparisons[4].to_columns()[5], "removed");
+
+        let comparisons = compare(old(), new(), None, None, true);
+        assert_eq!(names(&comparisons), vec!["b", "c", "a", "new", "old"]);
+
+        let comparisons = compare(old(), new(), None, Some(5.0), true);
+        assert_eq!(names(&comparisons), vec!["b", "c", "new", "old"]);
+
+        let filter = tantivy_query_grammar::parse_query("bench_name:b").unwrap();
+        let comparisons = compare(old(), new(), Some(&filter), None, false);
+        assert_eq!(names(&comparisons), vec!["b"]);
+    }
+
+    #[test]
+    fn results_are_matched_by_file_name() {
+        let in_binary = |binary: &str, median_ns: u64| {
+            let mut result = saved("bench", median_ns);
+            result.file_name = format!("{}.group_bench.0b9db5f05128889e", binary);
+            result
+        };
+        let old = vec![in_binary("first", 100), in_binary("second", 200)];
+        let new = vec![in_binary("second", 300), in_binary("first", 150)];
+        let comparisons = compare(old, new, None, None, false);
+        assert_eq!(comparisons.len(), 2);
+        assert_eq!(comparisons[0].median_change_pct(), Some(50.0));
+        assert_eq!(comparisons[1].median_change_pct(), Some(50.0));
+        assert_eq!(comparisons[0].to_columns()[0], "group/bench [first]");
+
+        // Legacy file names are matched by bench id
+        let comparisons = compare(
+            vec![saved("bench", 100)],
+            vec![in_binary("first", 110)],
+            None,
+            None,
+            false,
+        );
+        assert_eq!(comparisons.len(), 1);
+        assert_eq!(comparisons[0].to_columns()[0], "group/bench");
+    }
+
+    #[test]
+    fn display_name_test() {
+        let bench_id = BenchId::from_bench_name("bench").runner_name(Some("runner"));
+        assert_eq!(display_name(&bench_id), "runner/bench");
+    }
+}
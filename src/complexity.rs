@@ -0,0 +1,386 @@
+use std::fmt::{self, Display, Formatter};
+
+use yansi::Paint;
+
+use crate::{
+    BenchGroup,
+    bench_id::BenchId,
+    bench_runner::BenchRunner,
+    history::print_trends,
+    output_value::OutputValue,
+    report::{
+        format::{format_duration, format_float, format_with_underscores},
+        print_info,
+    },
+};
+
+/// The candidate complexity models which are fitted against the measured times.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Complexity {
+    /// O(1)
+    Constant,
+    /// O(log n)
+    Logarithmic,
+    /// O(n)
+    Linear,
+    /// O(n log n)
+    Linearithmic,
+    /// O(n²)
+    Quadratic,
+}
+
+impl Complexity {
+    /// All complexity models that are considered when fitting.
+    pub const ALL: [Complexity; 5] = [
+        Complexity::Constant,
+        Complexity::Logarithmic,
+        Complexity::Linear,
+        Complexity::Linearithmic,
+        Complexity::Quadratic,
+    ];
+
+    /// Evaluates the model function for the parameter `n`.
+    pub fn eval(self, n: f64) -> f64 {
+        match self {
+            Complexity::Constant => 1.0,
+            Complexity::Logarithmic => n.max(1.0).log2(),
+            Complexity::Linear => n,
+            Complexity::Linearithmic => n * n.max(1.0).log2(),
+            Complexity::Quadratic => n * n,
+        }
+    }
+}
+
+impl Display for Complexity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let val = match self {
+            Complexity::Constant => "O(1)",
+            Complexity::Logarithmic => "O(log n)",
+            Complexity::Linear => "O(n)",
+            Complexity::Linearithmic => "O(n log n)",
+            Complexity::Quadratic => "O(n²)",
+        };
+        write!(f, "{}", val)
+    }
+}
+
+/// The result of fitting the complexity models to the time series of a bench.
+#[derive(Debug, Clone)]
+pub struct ComplexityFit {
+    /// The name of the benchmark.
+    pub bench_name: String,
+    /// The measured median time in nanoseconds for each parameter.
+    pub series: Vec<(usize, u64)>,
+    /// The complexity model with the lowest error.
+    pub complexity: Complexity,
+    /// The fitted coefficient in nanoseconds, i.e. `time ≈ coefficient * f(n)`.
+    pub coefficient_ns: f64,
+    /// The normalized root mean square error of the best fit.
+    /// `0.05` means the fitted curve deviates 5% from the measured values on average.
+    pub rms: f64,
+}
+
+/// Fits all [Complexity] models to the series of `(n, time_ns)` via least squares and returns
+/// the model with the lowest normalized RMS error together with its coefficient and error.
+///
+/// Returns `None` if there are less than two data points.
+pub fn fit_complexity(series: &[(usize, u64)]) -> Option<(Complexity, f64, f64)> {
+    if series.len() < 2 {
+        return None;
+    }
+    let mean = series.iter().map(|(_, t)| *t as f64).sum::<f64>() / series.len() as f64;
+    if mean == 0.0 {
+        return None;
+    }
+    let mut best: Option<(Complexity, f64, f64)> = None;
+    for complexity in Complexity::ALL {
+        let sum_ff: f64 = series
+            .iter()
+            .map(|(n, _)| complexity.eval(*n as f64).powi(2))
+            .sum();
+        if sum_ff == 0.0 {
+            continue;
+        }
+        let sum_ft: f64 = series
+            .iter()
+            .map(|(n, t)| complexity.eval(*n as f64) * *t as f64)
+            .sum();
+        let coefficient = sum_ft / sum_ff;
+        let squared_error: f64 = series
+            .iter()
+            .map(|(n, t)| (*t as f64 - coefficient * complexity.eval(*n as f64)).powi(2))
+            .sum();
+        let rms = (squared_error / series.len() as f64).sqrt() / mean;
+        if best.is_none_or(|(_, _, best_rms)| rms < best_rms) {
+            best = Some((complexity, coefficient, rms));
+        }
+    }
+    best
+}
+
+type Registration<I> = Box<dyn for<'a, 'r> Fn(&mut BenchGroup<'a, 'r>, &'a I)>;
+type InputSize<I> = Box<dyn Fn(usize, &I) -> usize>;
+
+/// `ComplexityGroup` runs the same benchmarks over a range of numeric parameters and fits
+/// [Complexity] models to the measured median times.
+///
+/// Each parameter is run as its own group, so the time series is reported like any other group.
+/// After all parameters have been run, the best fitting complexity is reported for each bench.
+///
+/// # Example
+/// ```rust
+/// use binggan::{black_box, BenchRunner, Complexity};
+///
+/// let mut runner = BenchRunner::new();
+/// let mut group = runner.new_complexity_group([100, 1_000, 10_000], |n| {
+///     (0..n as u64).rev().collect::<Vec<u64>>()
+/// });
+/// group.set_name("sort");
+/// group.expect_complexity(Complexity::Linearithmic);
+/// group.register("sort_unstable", |data| {
+///     let mut data = data.clone();
+///     data.sort_unstable();
+///     black_box(data);
+/// });
+/// // let fits = group.run();
+/// ```
+pub struct ComplexityGroup<'runner, I> {
+    name: Option<String>,
+    params: Vec<usize>,
+    /// The input of each parameter. Empty if only the trends are printed.
+    inputs: Vec<I>,
+    /// The bench names and their registrations.
+    benches: Vec<(String, Registration<I>)>,
+    input_size: Option<InputSize<I>>,
+    expected_complexity: Option<Complexity>,
+    runner: &'runner mut BenchRunner,
+}
+
+impl<'runner, I> ComplexityGroup<'runner, I> {
+    /// Create a new ComplexityGroup. The inputs are created with `make_input` for each parameter.
+    pub fn new<P, F>(runner: &'runner mut BenchRunner, params: P, make_input: F) -> Self
+    where
+        P: IntoIterator<Item = usize>,
+        F: Fn(usize) -> I,
+    {
+        let params: Vec<usize> = params.into_iter().collect();
+        // The trends are read from the history, so the inputs aren't needed
+        let inputs = if runner.config.trend.is_some() {
+            Vec::new()
+        } else {
+            params.iter().map(|n| make_input(*n)).collect()
+        };
+        Self {
+            name: None,
+            params,
+            inputs,
+            benches: Vec::new(),
+            input_size: None,
+            expected_complexity: None,
+            runner,
+        }
+    }
+
+    /// Set the name of the group.
+    /// The name is prefixed to the group name of each parameter, e.g. `sort n=1_000`.
+    pub fn set_name<S: AsRef<str>>(&mut self, name: S) {
+        self.name = Some(name.as_ref().into());
+    }
+
+    /// Enables throughput reporting.
+    /// The passed closure should return the size of the input in bytes for the given parameter.
+    pub fn throughput<F>(&mut self, f: F)
+    where
+        F: Fn(usize, &I) -> usize + 'static,
+    {
+        self.input_size = Some(Box::new(f));
+    }
+
+    /// Assert that all benches in this group fit the expected complexity best.
+    ///
+    /// `run` will panic after reporting if the best fit differs.
+    pub fn expect_complexity(&mut self, complexity: Complexity) {
+        self.expected_complexity = Some(complexity);
+    }
+
+    /// Register a benchmark with the given name and function.
+    ///
+    /// The return value of the function will be reported as the `OutputValue`.
+    pub fn register<F, S: Into<String>, O: OutputValue + 'static>(&mut self, name: S, fun: F)
+    where
+        F: Fn(&I) -> O + Clone + 'static,
+    {
+        let name = name.into();
+        let bench_name = name.clone();
+        self.benches.push((
+            name,
+            Box::new(move |group, input| {
+                group.register_with_input(bench_name.clone(), input, fun.clone());
+            }),
+        ));
+    }
+
+    fn group_name(&self, n: usize) -> String {
+        let n = format_with_underscores(n as u64);
+        match &self.name {
+            Some(name) => format!("{} n={}", name, n),
+            None => format!("n={}", n),
+        }
+    }
+
+    /// Run the benchmarks for each parameter, report the results and the fitted complexities.
+    ///
+    /// Returns the fitted complexity for each bench.
+    pub fn run(&mut self) -> Vec<ComplexityFit> {
+        if let Some(last_n) = self.runner.config.trend {
+            for n in &self.params {
+                let group_name = self.group_name(*n);
+                let bench_ids: Vec<BenchId> = self
+                    .benches
+                    .iter()
+                    .map(|(bench_name, _)| {
+                        BenchId::from_bench_name(bench_name.clone())
+                            .runner_name(self.runner.name.as_deref())
+                            .group_name(Some(group_name.clone()))
+                    })
+                    .filter(|bench_id| self.runner.matches_filter(bench_id))
+                    .collect();
+                if !bench_ids.is_empty() {
+                    print_trends(Some(&group_name), &bench_ids, last_n);
+                }
+            }
+            return Vec::new();
+        }
+        let mut series_per_bench: Vec<(String, Vec<(usize, u64)>)> = Vec::new();
+        for (n, input) in self.params.iter().zip(&self.inputs) {
+            let group_name = self.group_name(*n);
+            let mut group = BenchGroup::new(self.runner);
+            group.set_name(group_name);
+            if let Some(input_size) = &self.input_size {
+                group.set_input_size(input_size(*n, input));
+            }
+            for (_, register) in &self.benches {
+                register(&mut group, input);
+            }
+            for result in group.run_and_get_results() {
+                let bench_name = result.bench_id.bench_name;
+                let point = (*n, result.stats.median_ns);
+                match series_per_bench
+                    .iter_mut()
+                    .find(|(name, _)| *name == bench_name)
+                {
+                    Some((_, series)) => series.push(point),
+                    None => series_per_bench.push((bench_name, vec![point])),
+                }
+            }
+        }
+
+        let fits: Vec<ComplexityFit> = series_per_bench
+            .into_iter()
+            .filter_map(|(bench_name, series)| {
+                let (complexity, coefficient_ns, rms) = fit_complexity(&series)?;
+                Some(ComplexityFit {
+                    bench_name,
+                    series,
+                    complexity,
+                    coefficient_ns,
+                    rms,
+                })
+            })
+            .collect();
+        if fits.is_empty() {
+            return fits;
+        }
+        print_fits(self.name.as_deref(), &fits);
+
+        if let Some(expected) = self.expected_complexity {
+            let mismatches: Vec<String> = fits
+                .iter()
+                .filter(|fit| fit.complexity != expected)
+                .map(|fit| format!("{} is {}", fit.bench_name, fit.complexity))
+                .collect();
+            assert!(
+                mismatches.is_empty(),
+                "Expected complexity {}, but {}",
+                expected,
+                mismatches.join(", ")
+            );
+        }
+        fits
+    }
+}
+
+fn print_fits(name: Option<&str>, fits: &[ComplexityFit]) {
+    let title = match name {
+        Some(name) => format!("{} complexity", name),
+        None => "Complexity".to_string(),
+    };
+    print_info!("{}", title.black().on_yellow().invert().bold());
+    let name_width = fits
+        .iter()
+        .map(|fit| fit.bench_name.len())
+        .max()
+        .unwrap_or(0);
+    for fit in fits {
+        let series = fit
+            .series
+            .iter()
+            .map(|(n, ns)| {
+                format!(
+                    "{}: {}",
+                    format_with_underscores(*n as u64),
+                    format_duration(*ns)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        print_info!(
+            "{:<name_width$}    {:<10}    Coef: {}ns    RMS: {:.2}%    [{}]",
+            fit.bench_name,
+            fit.complexity.bold(),
+            format_float(fit.coefficient_ns),
+            fit.rms * 100.0,
+            series,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn series(f: impl Fn(f64) -> f64) -> Vec<(usize, u64)> {
+        [1_000, 10_000, 100_000, 1_000_000]
+            .into_iter()
+            .map(|n| (n, f(n as f64) as u64))
+            .collect()
+    }
+
+    #[test]
+    fn fit_complexity_finds_model() {
+        let cases = [
+            (series(|_| 500.0), Complexity::Constant),
+            (series(|n| 20.0 * n.log2()), Complexity::Logarithmic),
+            (series(|n| 3.0 * n), Complexity::Linear),
+            (series(|n| 2.0 * n * n.log2()), Complexity::Linearithmic),
+            (series(|n| 0.5 * n * n), Complexity::Quadratic),
+        ];
+        for (series, expected) in cases {
+            let (complexity, _coef, rms) = fit_complexity(&series).unwrap();
+            assert_eq!(complexity, expected);
+            assert!(rms < 0.01, "rms {} for {}", rms, expected);
+        }
+    }
+
+    #[test]
+    fn fit_complexity_coefficient() {
+        let (complexity, coef, _rms) = fit_complexity(&series(|n| 3.0 * n)).unwrap();
+        assert_eq!(complexity, Complexity::Linear);
+        assert!((coef - 3.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn fit_complexity_needs_two_points() {
+        assert!(fit_complexity(&[(10, 100)]).is_none());
+    }
+}
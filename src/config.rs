@@ -1,5 +1,12 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
 use rustop::opts;
 
+use crate::calibration::timer_calibration;
+use crate::preflight::default_max_load_avg;
+use crate::report::OutputFormat;
+
 /// Configure the benchmarking options.
 #[derive(Debug, Clone)]
 pub struct Config {
@@ -22,6 +29,39 @@ pub struct Config {
     /// Intended for single-threaded, single-benchmark runs.
     /// Assumes a single thread is doing work during the measurement.
     pub adjust_for_single_threaded_cpu_scheduling: bool,
+    /// Subtract the calibrated timer overhead from the measured time. Enabled by default.
+    ///
+    /// The overhead of a timer read is measured once at startup by timing an empty closure.
+    /// It is mostly relevant for benches in the nanosecond range.
+    pub subtract_timer_overhead: bool,
+    /// Check the system for noisy conditions before the first group is run and warn about
+    /// them, see [NoiseFinding](crate::NoiseFinding). Enabled by default.
+    pub preflight_check: bool,
+    /// The 1 minute load average above which the system is considered noisy.
+    /// Defaults to half the number of cores, but at least 1.
+    pub max_load_avg: f64,
+    /// Wait up to the given duration before the first group is run, until the load average
+    /// drops to [Config::max_load_avg].
+    pub wait_for_low_load: Option<Duration>,
+    /// Additionally save the results as the named baseline.
+    /// This is read from the command line `--save-baseline <name>` by default.
+    pub save_baseline: Option<String>,
+    /// Compare against the named baseline instead of the last run.
+    /// This is read from the command line `--baseline <name>` by default.
+    pub baseline: Option<String>,
+    /// Print the trend of the last N runs of each bench instead of running the benches.
+    /// This is read from the command line `--trend <N>` by default.
+    pub trend: Option<usize>,
+    /// Store the raw samples of each bench in the result file, i.e. the time, perf counters
+    /// and memory of each group iteration. See [RawSamples](crate::report::RawSamples).
+    /// This is read from the command line `--store-raw-samples` by default.
+    pub store_raw_samples: bool,
+    /// The output format of the results.
+    /// This is read from the command line `--output-format <format>` by default.
+    pub output_format: OutputFormat,
+    /// Write machine-readable output formats to this file instead of stdout.
+    /// This is read from the command line `--output-path <path>` by default.
+    pub output_path: Option<PathBuf>,
 }
 
 impl Default for Config {
@@ -36,6 +76,16 @@ impl Default for Config {
             num_iter_bench: None,
             num_iter_group: None,
             adjust_for_single_threaded_cpu_scheduling: false,
+            subtract_timer_overhead: true,
+            preflight_check: true,
+            max_load_avg: default_max_load_avg(),
+            wait_for_low_load: None,
+            save_baseline: None,
+            baseline: None,
+            trend: None,
+            store_raw_samples: false,
+            output_format: OutputFormat::Plain,
+            output_path: None,
         }
     }
 }
@@ -103,6 +153,96 @@ impl Config {
         self.adjust_for_single_threaded_cpu_scheduling = enabled;
         self
     }
+
+    /// Subtract the calibrated timer overhead from the measured time. Enabled by default.
+    ///
+    /// The resolution and overhead of the timer are printed in verbose mode.
+    pub fn set_subtract_timer_overhead(&mut self, enabled: bool) -> &mut Self {
+        self.subtract_timer_overhead = enabled;
+        self
+    }
+
+    /// Check the system for noisy conditions before the first group is run. Enabled by default.
+    ///
+    /// Checks the CPU frequency governor, turbo boost, the load average and whether
+    /// `perf_event_paranoid` blocks the [PerfCounterPlugin](crate::plugins::PerfCounterPlugin).
+    pub fn set_preflight_check(&mut self, enabled: bool) -> &mut Self {
+        self.preflight_check = enabled;
+        self
+    }
+
+    /// Set the 1 minute load average above which the system is considered noisy.
+    pub fn set_max_load_avg(&mut self, max_load_avg: f64) -> &mut Self {
+        self.max_load_avg = max_load_avg;
+        self
+    }
+
+    /// Wait up to `timeout` before the first group is run, until the load average drops to
+    /// [Config::max_load_avg].
+    pub fn set_wait_for_low_load(&mut self, timeout: Duration) -> &mut Self {
+        self.wait_for_low_load = Some(timeout);
+        self
+    }
+
+    /// Additionally save the results as the named baseline, e.g. `main`.
+    ///
+    /// The results of the last run are always saved, so the default comparison against the last
+    /// run is not affected. Characters other than ASCII alphanumerics, `-` and `_` in the name
+    /// are replaced by `_`.
+    pub fn set_save_baseline<S: Into<String>>(&mut self, name: S) -> &mut Self {
+        self.save_baseline = Some(name.into());
+        self
+    }
+
+    /// Compare against the named baseline instead of the last run.
+    ///
+    /// The baseline is not overwritten, so multiple runs can be compared against it.
+    pub fn set_baseline<S: Into<String>>(&mut self, name: S) -> &mut Self {
+        self.baseline = Some(name.into());
+        self
+    }
+
+    /// Print the trend of the last `last_n` runs of each bench instead of running the benches.
+    ///
+    /// The trend shows the min and median of the median times and a sparkline.
+    pub fn set_trend(&mut self, last_n: usize) -> &mut Self {
+        self.trend = Some(last_n);
+        self
+    }
+
+    /// Store the raw samples of each bench in the result file.
+    ///
+    /// They are available as [BenchResult::raw_samples](crate::BenchResult::raw_samples) for the
+    /// current run and as [BenchResult::old_raw_samples](crate::BenchResult::old_raw_samples)
+    /// for the compared run, e.g. for statistical tests or plots.
+    pub fn set_store_raw_samples(&mut self, enabled: bool) -> &mut Self {
+        self.store_raw_samples = enabled;
+        self
+    }
+
+    /// Set the output format of the results. Machine-readable formats are printed to stdout,
+    /// unless [Config::set_output_path] is set.
+    ///
+    /// The reporter for the format is added before the first group is run.
+    pub fn set_output_format(&mut self, output_format: OutputFormat) -> &mut Self {
+        self.output_format = output_format;
+        self
+    }
+
+    /// Write machine-readable output formats to the file at `path` instead of stdout.
+    pub fn set_output_path<P: Into<PathBuf>>(&mut self, path: P) -> &mut Self {
+        self.output_path = Some(path.into());
+        self
+    }
+
+    /// Returns the timer overhead per sample, which is subtracted from the measured time.
+    pub(crate) fn timer_overhead_ns(&self) -> f64 {
+        if self.subtract_timer_overhead {
+            timer_calibration().overhead_ns
+        } else {
+            0.0
+        }
+    }
 }
 
 /// Parses a benchmark iteration override from an environment variable.
@@ -120,14 +260,35 @@ pub(crate) fn parse_args() -> Config {
                          This may lead to better results, it may also lead to worse results.
                          It very much depends on the benches and the environment you would like to simulate. ";
         opt exact:bool, desc:"Filter benchmarks by exact name rather than by pattern.";
+        opt save_baseline:Option<String>, desc:"Additionally save the results as the named baseline.";
+        opt baseline:Option<String>, desc:"Compare against the named baseline instead of the last run.";
+        opt trend:Option<usize>, desc:"Print the trend of the last N runs of each bench instead of running them.";
+        opt store_raw_samples:bool, desc:"Store the raw samples of each bench in the result file.";
+        opt output_format:Option<String>, desc:"The output format: plain, json or markdown.";
+        opt output_path:Option<String>, desc:"Write machine-readable output to this file instead of stdout.";
         param filter:Option<String>, desc:"run only bench matching filter. Supports AND/OR and fields like runner_name, group_name, bench_name."; // an optional positional parameter
     }
     .parse();
     if let Ok((args, _rest)) = res {
         let default_config = Config::default();
+        let output_format = args
+            .output_format
+            .map(|format| {
+                format.parse().unwrap_or_else(|err| {
+                    println!("{}", err);
+                    OutputFormat::Plain
+                })
+            })
+            .unwrap_or_default();
         Config {
             interleave: args.interleave,
             filter: args.filter.or(default_config.filter),
+            save_baseline: args.save_baseline,
+            baseline: args.baseline,
+            trend: args.trend,
+            store_raw_samples: args.store_raw_samples,
+            output_format,
+            output_path: args.output_path.map(PathBuf::from),
             ..default_config
         }
     } else if let Err(rustop::Error::Help(help)) = res {
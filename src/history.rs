@@ -0,0 +1,204 @@
+//! Append-only history of the results of each bench.
+//!
+//! Every run appends a line to `history/<file name>.jsonl` in the output directory, see
+//! [get_bench_file_name]. The history is used to show the trend of a bench over the last runs,
+//! which reveals slow creep that a run-to-run comparison never shows.
+
+use std::io::Write;
+use std::path::PathBuf;
+
+use miniserde::{Deserialize, Serialize};
+use yansi::Paint;
+
+use crate::{
+    RunMetadata,
+    bench::BenchResult,
+    bench_id::BenchId,
+    report::{format::format_duration, print_info, print_table},
+    stats::BenchStats,
+    write_results::{get_bench_file_name, get_output_directory, migrate_unnamespaced_file},
+};
+
+/// A single run of a bench in the history.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct HistoryEntry {
+    /// The aggregated statistics of the run.
+    pub stats: BenchStats,
+    /// The serialized output value of the run, see [OutputValue::serialize](crate::OutputValue::serialize).
+    pub output_value: Option<String>,
+    /// The environment of the run.
+    pub metadata: Option<RunMetadata>,
+}
+
+fn get_history_file(bench_id: &BenchId) -> PathBuf {
+    let history_directory = get_output_directory().join("history");
+    let filepath = history_directory.join(format!("{}.jsonl", get_bench_file_name(bench_id)));
+    migrate_unnamespaced_file(
+        &history_directory.join(format!("{}.jsonl", bench_id.get_full_name())),
+        &filepath,
+    );
+    filepath
+}
+
+/// Appends the result to the history of the bench.
+pub(crate) fn append_to_history(result: &BenchResult) -> std::io::Result<()> {
+    let entry = HistoryEntry {
+        stats: result.stats,
+        output_value: result.serialized_output_value.clone(),
+        metadata: result.metadata.clone(),
+    };
+    let filepath = get_history_file(&result.bench_id);
+    if let Some(parent) = filepath.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(filepath)?;
+    // Write the line at once, so concurrent appends don't interleave
+    let mut line = miniserde::json::to_string(&entry);
+    line.push('\n');
+    file.write_all(line.as_bytes())
+}
+
+/// Reads the history of the bench, oldest first. Unreadable lines are skipped.
+pub fn read_history(bench_id: &BenchId) -> Vec<HistoryEntry> {
+    let Ok(content) = std::fs::read_to_string(get_history_file(bench_id)) else {
+        return Vec::new();
+    };
+    content
+        .lines()
+        .filter_map(|line| miniserde::json::from_str(line).ok())
+        .collect()
+}
+
+/// The trend of a bench over the last runs.
+#[derive(Debug, Clone)]
+pub struct BenchTrend {
+    /// The bench id.
+    pub bench_id: BenchId,
+    /// The last runs, oldest first.
+    pub entries: Vec<HistoryEntry>,
+}
+
+impl BenchTrend {
+    /// Loads the trend of the last `last_n` runs of the bench.
+    pub fn load(bench_id: &BenchId, last_n: usize) -> Self {
+        let mut entries = read_history(bench_id);
+        let skip = entries.len().saturating_sub(last_n);
+        entries.drain(..skip);
+        BenchTrend {
+            bench_id: bench_id.clone(),
+            entries,
+        }
+    }
+
+    /// The median time of each run, oldest first.
+    pub fn medians_ns(&self) -> Vec<u64> {
+        self.entries
+            .iter()
+            .map(|entry| entry.stats.median_ns)
+            .collect()
+    }
+
+    /// The fastest median time of the runs.
+    pub fn min_ns(&self) -> Option<u64> {
+        self.medians_ns().into_iter().min()
+    }
+
+    /// The median of the median times of the runs.
+    pub fn median_ns(&self) -> Option<u64> {
+        let mut medians = self.medians_ns();
+        medians.sort_unstable();
+        medians.get(medians.len() / 2).copied()
+    }
+
+    /// A sparkline of the median times of the runs, oldest first.
+    pub fn sparkline(&self) -> String {
+        sparkline(&self.medians_ns())
+    }
+
+    /// Returns the trend as a row of columns to print.
+    pub(crate) fn to_columns(&self) -> Vec<String> {
+        let (Some(min_ns), Some(median_ns)) = (self.min_ns(), self.median_ns()) else {
+            return vec![self.bench_id.bench_name.clone(), "No history".to_string()];
+        };
+        let latest_ns = self.entries.last().unwrap().stats.median_ns;
+        vec![
+            self.bench_id.bench_name.clone(),
+            format!("Runs: {}", self.entries.len()),
+            format!("Min: {}", format_duration(min_ns)),
+            format!("Median: {}", format_duration(median_ns)),
+            format!("Latest: {}", format_duration(latest_ns)),
+            self.sparkline().cyan().to_string(),
+        ]
+    }
+}
+
+/// Prints the trends of the last `last_n` runs of the benches of a group.
+pub(crate) fn print_trends(group_name: Option<&str>, bench_ids: &[BenchId], last_n: usize) {
+    if let Some(group_name) = group_name {
+        print_info!("{}", group_name.black().on_yellow().invert().bold());
+    }
+    let rows: Vec<Vec<String>> = bench_ids
+        .iter()
+        .map(|bench_id| BenchTrend::load(bench_id, last_n).to_columns())
+        .collect();
+    print_table(&rows);
+}
+
+/// Renders the values as sparkline, scaled between the min and max value.
+fn sparkline(values: &[u64]) -> String {
+    const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    let (Some(min), Some(max)) = (values.iter().min(), values.iter().max()) else {
+        return String::new();
+    };
+    let range = (max - min) as f64;
+    values
+        .iter()
+        .map(|value| {
+            if range == 0.0 {
+                return BARS[0];
+            }
+            let idx = ((value - min) as f64 / range * (BARS.len() - 1) as f64).round() as usize;
+            BARS[idx]
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(median_ns: u64) -> HistoryEntry {
+        HistoryEntry {
+            stats: BenchStats {
+                min_ns: median_ns,
+                max_ns: median_ns,
+                average_ns: median_ns,
+                median_ns,
+                avg_memory: 0,
+            },
+            output_value: None,
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn sparkline_test() {
+        assert_eq!(sparkline(&[]), "");
+        assert_eq!(sparkline(&[5, 5]), "▁▁");
+        assert_eq!(sparkline(&[10, 20, 15, 80]), "▁▂▂█");
+    }
+
+    #[test]
+    fn trend_stats() {
+        let trend = BenchTrend {
+            bench_id: BenchId::from_bench_name("bench"),
+            entries: [30, 10, 20, 40].into_iter().map(entry).collect(),
+        };
+        assert_eq!(trend.min_ns(), Some(10));
+        assert_eq!(trend.median_ns(), Some(30));
+        assert_eq!(trend.sparkline(), "▆▁▃█");
+    }
+}
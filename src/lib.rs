@@ -21,9 +21,32 @@
 //!
 //! Otherwise if you need more flexibility you can use [BenchGroup] via [BenchRunner::new_group](crate::BenchRunner::new).
 //!
+//! To check how a benchmark scales with its input size, use [ComplexityGroup] via
+//! [BenchRunner::new_complexity_group]. It runs the benchmarks over a range of parameters and
+//! fits [Complexity] models to the measured times.
+//!
 //! See <https://github.com/PSeitz/binggan/tree/main/benches> for examples. `benches/bench_group.rs` and
 //! `benches/bench_input_group.rs` are different ways to produce the same output.
 //!
+//! ## History
+//! The results of every run are appended to a history per bench. Run the bench binary with
+//! `--trend <N>` to print the trend of the last N runs instead of running the benches, or use
+//! [BenchTrend] to load it.
+//!
+//! ## Custom Measurement
+//! Benchmarks registered with a bencher variant, e.g. [BenchGroup::register_with_bencher],
+//! receive a [Bencher]. It allows to exclude setup from the measurement or to report a custom
+//! measured duration.
+//!
+//! ## External Commands
+//! [BenchGroup::register_command] benchmarks an external command, similar to `hyperfine`. This
+//! allows to compare a CLI tool with the library it uses in the same report.
+//!
+//! ## Macros
+//! With the `macros` feature, benches can be declared with the `#[binggan::bench]` attribute
+//! and run with `binggan::main!()`, which removes the boilerplate of defining the global
+//! allocator, the runner and the groups. See `benches/bench_macro.rs` for an example.
+//!
 //! ## OutputValue
 //! The typical benchmarking flow involves providing some input, processing it through a function, and obtaining an output.
 //! Benchmarks return [OutputValue], which represents the result of the benchmark. This output can be particularly
@@ -180,16 +203,53 @@ pub(crate) mod write_results;
 
 mod bench_group;
 mod bench_input_group;
+mod bencher;
+mod calibration;
+mod command;
+#[cfg(feature = "cli")]
+mod compare;
+mod complexity;
 mod config;
+mod history;
+#[cfg(feature = "macros")]
+mod macro_support;
+#[cfg(feature = "cli")]
+mod merge;
+mod metadata;
+mod preflight;
 
 pub use bench::BenchResult;
 pub use bench_group::BenchGroup;
 pub use bench_id::BenchId;
 pub use bench_input_group::InputGroup;
 pub use bench_runner::BenchRunner;
+pub use bencher::Bencher;
+pub use command::{BenchCommand, CommandUsage};
+pub use complexity::{Complexity, ComplexityFit, ComplexityGroup, fit_complexity};
 pub use config::Config;
+pub use history::{BenchTrend, HistoryEntry, read_history};
+pub use metadata::RunMetadata;
 pub use output_value::OutputValue;
 pub use peakmem_alloc::*;
+pub use preflight::NoiseFinding;
+
+#[cfg_attr(docsrs, doc(cfg(feature = "macros")))]
+#[cfg(feature = "macros")]
+pub use binggan_macros::bench;
+
+/// Used by the code generated by `#[binggan::bench]` and `binggan::main!()`. Not public API.
+#[cfg(feature = "macros")]
+#[doc(hidden)]
+pub mod __private {
+    pub use crate::macro_support::*;
+}
+
+/// Used by the `binggan` binary. Not public API.
+#[cfg(feature = "cli")]
+#[doc(hidden)]
+pub mod __cli {
+    pub use crate::compare::run_cli;
+}
 
 pub(crate) use config::parse_args;
 
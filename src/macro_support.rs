@@ -0,0 +1,161 @@
+//! Runtime support for `#[binggan::bench]` and `binggan::main!()`.
+//!
+//! The attribute macro registers a [BenchEntry] for each annotated function in [BENCHES].
+//! `main!` creates a [BenchRunner] and runs all registered entries via [run_registered_benches].
+
+use std::any::Any;
+
+use crate::{BenchGroup, BenchRunner};
+
+pub use linkme;
+
+/// All benches registered via `#[binggan::bench]`.
+#[linkme::distributed_slice]
+pub static BENCHES: [BenchEntry];
+
+/// A bench registered via `#[binggan::bench]`.
+#[derive(Clone, Copy)]
+pub struct BenchEntry {
+    /// The name of the bench.
+    pub name: &'static str,
+    /// Benches with the same group are run together.
+    pub group: Option<&'static str>,
+    /// The source file of the bench. Used to order the benches.
+    pub file: &'static str,
+    /// The line of the bench in the source file. Used to order the benches.
+    pub line: u32,
+    /// Creates the input of the bench.
+    pub make_input: fn() -> Box<dyn Any>,
+    /// Returns the size of the input in bytes. Enables throughput reporting.
+    pub input_size: Option<fn(&dyn Any) -> usize>,
+    /// Registers the bench with its input on the group.
+    pub register: for<'a, 'r> fn(&mut BenchGroup<'a, 'r>, &'a dyn Any),
+}
+
+/// Downcasts the type erased input to the input type of the bench.
+pub fn downcast_input<T: 'static>(input: &dyn Any) -> &T {
+    input
+        .downcast_ref::<T>()
+        .expect("binggan: bench input has an unexpected type")
+}
+
+/// Runs all benches registered via `#[binggan::bench]`.
+///
+/// Benches are ordered by their position in the source. Benches with the same group name are
+/// run together in one group. The inputs of a group are created just before the group runs and
+/// dropped right after.
+pub fn run_registered_benches(runner: &mut BenchRunner) {
+    for (group_name, entries) in group_entries(&BENCHES) {
+        let inputs: Vec<Box<dyn Any>> = entries.iter().map(|entry| (entry.make_input)()).collect();
+        let mut group = runner.new_group();
+        if let Some(group_name) = group_name {
+            group.set_name(group_name);
+        }
+        for (entry, input) in entries.iter().zip(inputs.iter()) {
+            let input_size = entry.input_size.map(|input_size| input_size(&**input));
+            group.input_size_in_bytes = input_size;
+            (entry.register)(&mut group, &**input);
+        }
+        group.run();
+    }
+}
+
+/// Sorts the entries by source position and groups them by group name in the order of their
+/// first appearance.
+fn group_entries(entries: &[BenchEntry]) -> Vec<(Option<&'static str>, Vec<&BenchEntry>)> {
+    let mut sorted: Vec<&BenchEntry> = entries.iter().collect();
+    sorted.sort_by_key(|entry| (entry.file, entry.line));
+    let mut groups: Vec<(Option<&'static str>, Vec<&BenchEntry>)> = Vec::new();
+    for entry in sorted {
+        match groups.iter_mut().find(|(group, _)| *group == entry.group) {
+            Some((_, group_entries)) => group_entries.push(entry),
+            None => groups.push((entry.group, vec![entry])),
+        }
+    }
+    groups
+}
+
+/// Defines the `main` function and the global allocator to run all benches declared with
+/// `#[binggan::bench]`.
+///
+/// The benches are run on a [BenchRunner](crate::BenchRunner) with the
+/// [PeakMemAllocPlugin](crate::plugins::PeakMemAllocPlugin) and the
+/// [PerfCounterPlugin](crate::plugins::PerfCounterPlugin) enabled.
+/// An optional argument sets the name of the runner.
+///
+/// Since it defines the `#[global_allocator]`, the bench file must not define another one.
+/// The bench target still needs `harness = false` in `Cargo.toml`.
+///
+/// # Example
+/// ```ignore
+/// use binggan::black_box;
+///
+/// #[binggan::bench(group = "factorial")]
+/// fn factorial_100() -> usize {
+///     (1..=black_box(100usize)).fold(1, |acc, n| acc.wrapping_mul(n))
+/// }
+///
+/// binggan::main!("my benches");
+/// ```
+#[macro_export]
+macro_rules! main {
+    () => {
+        $crate::main!(@runner $crate::BenchRunner::new());
+    };
+    ($name:expr) => {
+        $crate::main!(@runner $crate::BenchRunner::with_name($name));
+    };
+    (@runner $runner:expr) => {
+        #[global_allocator]
+        static __BINGGAN_GLOBAL: &$crate::PeakMemAlloc<::std::alloc::System> =
+            &$crate::INSTRUMENTED_SYSTEM;
+
+        fn main() {
+            let mut runner = $runner;
+            runner
+                .add_plugin($crate::plugins::PeakMemAllocPlugin::new(__BINGGAN_GLOBAL))
+                .add_plugin($crate::plugins::PerfCounterPlugin::default());
+            $crate::__private::run_registered_benches(&mut runner);
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &'static str, group: Option<&'static str>, line: u32) -> BenchEntry {
+        BenchEntry {
+            name,
+            group,
+            file: "benches/bench.rs",
+            line,
+            make_input: || Box::new(()),
+            input_size: None,
+            register: |_group, _input| {},
+        }
+    }
+
+    #[test]
+    fn group_entries_by_position_and_group() {
+        let entries = [
+            entry("c", Some("g2"), 30),
+            entry("a", Some("g1"), 10),
+            entry("d", None, 40),
+            entry("b", Some("g2"), 20),
+            entry("e", Some("g1"), 50),
+        ];
+        let groups: Vec<(Option<&str>, Vec<&str>)> = group_entries(&entries)
+            .into_iter()
+            .map(|(group, entries)| (group, entries.iter().map(|e| e.name).collect()))
+            .collect();
+        assert_eq!(
+            groups,
+            vec![
+                (Some("g1"), vec!["a", "e"]),
+                (Some("g2"), vec!["b", "c"]),
+                (None, vec!["d"]),
+            ]
+        );
+    }
+}
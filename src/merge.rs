@@ -0,0 +1,234 @@
+//! Merging of the results of repeated runs of a bench binary, used by the `binggan` binary.
+//!
+//! Running the bench binary multiple times reduces the variance between processes, e.g. from
+//! memory layout or CPU frequency. Each run is saved as a named baseline and the baselines are
+//! merged per [BenchId] into a combined baseline.
+
+use std::str::FromStr;
+
+use crate::{
+    bench_id::BenchId,
+    plugins::PerfCounterValues,
+    report::RawSamples,
+    stats::{BenchStats, compute_stats_from_durations},
+    write_results::{ResultFile, SavedResult},
+};
+
+/// How the aggregated stats of the runs are combined, if not all runs stored raw samples.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) enum MergeStat {
+    /// The median of each value over the runs.
+    #[default]
+    Median,
+    /// The minimum of each value over the runs.
+    Min,
+}
+
+impl FromStr for MergeStat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "median" => Ok(MergeStat::Median),
+            "min" => Ok(MergeStat::Min),
+            _ => Err(format!(
+                "Invalid merge stat: {}. Expected one of: median, min",
+                s
+            )),
+        }
+    }
+}
+
+impl MergeStat {
+    fn aggregate_u64(self, mut values: Vec<u64>) -> u64 {
+        values.sort_unstable();
+        match self {
+            MergeStat::Median => values[values.len() / 2],
+            MergeStat::Min => values[0],
+        }
+    }
+
+    fn aggregate_f64(self, mut values: Vec<f64>) -> f64 {
+        values.sort_by(|a, b| a.total_cmp(b));
+        match self {
+            MergeStat::Median => values[values.len() / 2],
+            MergeStat::Min => values[0],
+        }
+    }
+}
+
+/// The merged result of a bench.
+#[derive(Debug, Clone)]
+pub(crate) struct MergedResult {
+    pub bench_id: BenchId,
+    pub file_name: String,
+    pub file: ResultFile,
+    /// The number of runs which contained the bench.
+    pub num_runs: usize,
+    /// Whether the stats were computed from the pooled raw samples.
+    pub pooled: bool,
+}
+
+/// Merges the results of multiple runs per bench, i.e. per file name, which is namespaced by
+/// the bench binary.
+///
+/// If all runs of a bench contain raw samples, they are pooled and the stats are computed from
+/// them. Otherwise each stats value is combined with `stat`. The output value, metadata and
+/// noise findings are taken from the last run.
+pub(crate) fn merge_results(runs: Vec<Vec<SavedResult>>, stat: MergeStat) -> Vec<MergedResult> {
+    let mut per_bench: Vec<(BenchId, String, Vec<ResultFile>)> = Vec::new();
+    for result in runs.into_iter().flatten() {
+        match per_bench
+            .iter_mut()
+            .find(|(_, file_name, _)| *file_name == result.file_name)
+        {
+            Some((_, _, files)) => files.push(result.file),
+            None => per_bench.push((result.bench_id, result.file_name, vec![result.file])),
+        }
+    }
+    let mut merged: Vec<MergedResult> = per_bench
+        .into_iter()
+        .map(|(bench_id, file_name, files)| merge_bench(bench_id, file_name, files, stat))
+        .collect();
+    merged.sort_by(|a, b| (&a.bench_id, &a.file_name).cmp(&(&b.bench_id, &b.file_name)));
+    merged
+}
+
+fn merge_bench(
+    bench_id: BenchId,
+    file_name: String,
+    files: Vec<ResultFile>,
+    stat: MergeStat,
+) -> MergedResult {
+    let num_runs = files.len();
+    let raw_samples: Option<Vec<&RawSamples>> = files
+        .iter()
+        .map(|file| {
+            file.raw_samples
+                .as_ref()
+                .filter(|samples| !samples.durations_ns.is_empty())
+        })
+        .collect();
+    let pooled_samples = raw_samples.map(|raw_samples| RawSamples {
+        durations_ns: raw_samples
+            .iter()
+            .flat_map(|samples| samples.durations_ns.iter().copied())
+            .collect(),
+        perf_counter: raw_samples
+            .iter()
+            .flat_map(|samples| samples.perf_counter.iter().cloned())
+            .collect(),
+        memory: raw_samples
+            .iter()
+            .flat_map(|samples| samples.memory.iter().copied())
+            .collect(),
+    });
+    let stats = match &pooled_samples {
+        Some(samples) => {
+            compute_stats_from_durations(samples.durations_ns.clone(), Some(&samples.memory))
+        }
+        None => {
+            let aggregate = |f: fn(&BenchStats) -> u64| {
+                stat.aggregate_u64(files.iter().map(|file| f(&file.stats)).collect())
+            };
+            BenchStats {
+                min_ns: aggregate(|stats| stats.min_ns),
+                max_ns: aggregate(|stats| stats.max_ns),
+                average_ns: aggregate(|stats| stats.average_ns),
+                median_ns: aggregate(|stats| stats.median_ns),
+                avg_memory: aggregate(|stats| stats.avg_memory as u64) as usize,
+            }
+        }
+    };
+    let perf_counters: Vec<&PerfCounterValues> = files
+        .iter()
+        .filter_map(|file| file.perf_counter.as_ref())
+        .collect();
+    let perf_counter = (!perf_counters.is_empty())
+        .then(|| PerfCounterValues::aggregate(&perf_counters, |values| stat.aggregate_f64(values)));
+
+    let pooled = pooled_samples.is_some();
+    let last = files.into_iter().last().unwrap();
+    MergedResult {
+        file: ResultFile {
+            bench_id: Some(bench_id.clone()),
+            stats,
+            perf_counter,
+            raw_samples: pooled_samples,
+            ..last
+        },
+        bench_id,
+        file_name,
+        num_runs,
+        pooled,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn saved(median_ns: u64, durations_ns: Option<Vec<u64>>) -> SavedResult {
+        let stats = BenchStats {
+            min_ns: median_ns - 1,
+            max_ns: median_ns + 1,
+            average_ns: median_ns,
+            median_ns,
+            avg_memory: 0,
+        };
+        let mut saved = SavedResult::for_test(BenchId::from_bench_name("bench"), stats);
+        saved.file.raw_samples = durations_ns.map(|durations_ns| RawSamples {
+            durations_ns,
+            perf_counter: Vec::new(),
+            memory: Vec::new(),
+        });
+        saved
+    }
+
+    #[test]
+    fn merge_aggregated_stats() {
+        let runs = || {
+            vec![
+                vec![saved(10, None)],
+                vec![saved(30, Some(vec![30]))],
+                vec![saved(20, None)],
+            ]
+        };
+        let merged = merge_results(runs(), MergeStat::Median);
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0].num_runs, 3);
+        assert!(!merged[0].pooled);
+        assert_eq!(merged[0].file.stats.median_ns, 20);
+        assert_eq!(merged[0].file.stats.max_ns, 21);
+
+        let merged = merge_results(runs(), MergeStat::Min);
+        assert_eq!(merged[0].file.stats.median_ns, 10);
+        assert_eq!(merged[0].file.stats.min_ns, 9);
+    }
+
+    #[test]
+    fn merge_pooled_samples() {
+        let runs = vec![
+            vec![saved(2, Some(vec![1, 2, 3]))],
+            vec![saved(20, Some(vec![10, 20, 30]))],
+        ];
+        let merged = merge_results(runs, MergeStat::Median);
+        assert!(merged[0].pooled);
+        let stats = merged[0].file.stats;
+        assert_eq!((stats.min_ns, stats.max_ns, stats.median_ns), (1, 30, 6));
+        let raw_samples = merged[0].file.raw_samples.as_ref().unwrap();
+        assert_eq!(raw_samples.durations_ns, vec![1, 2, 3, 10, 20, 30]);
+    }
+
+    #[test]
+    fn benches_of_different_binaries_are_not_merged() {
+        let mut other_binary = saved(20, None);
+        other_binary.file_name = format!("other.{}", other_binary.file_name);
+        let merged = merge_results(
+            vec![vec![saved(10, None)], vec![other_binary]],
+            MergeStat::Min,
+        );
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].num_runs, 1);
+    }
+}
@@ -0,0 +1,451 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use miniserde::{Deserialize, Serialize};
+
+use crate::Config;
+
+/// Metadata about the environment of a run. It is stored with the results of each bench.
+///
+/// Reporters show the differences, if the metadata of the current run differs from the one of
+/// the run it is compared with.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct RunMetadata {
+    /// The time of the run in seconds since the unix epoch.
+    pub timestamp: u64,
+    /// The git commit of the current directory.
+    pub git_commit: Option<String>,
+    /// Whether tracked files were modified, based on the file stats in the git index.
+    pub git_dirty: Option<bool>,
+    /// The rustc version. `RUSTC_VERSION` takes precedence over the version detected when
+    /// building binggan.
+    pub rustc_version: Option<String>,
+    /// The CPU model.
+    pub cpu_model: Option<String>,
+    /// The kernel or OS version.
+    pub kernel: Option<String>,
+    /// The number of available cores.
+    pub num_cores: usize,
+    /// The enabled cargo features of binggan.
+    pub features: Vec<String>,
+    /// The [Config] of the runner.
+    pub config: BTreeMap<String, String>,
+}
+
+/// The part of the metadata which is the same for all runners in a process.
+#[derive(Clone)]
+struct SystemMetadata {
+    timestamp: u64,
+    git_commit: Option<String>,
+    git_dirty: Option<bool>,
+    rustc_version: Option<String>,
+    cpu_model: Option<String>,
+    kernel: Option<String>,
+    num_cores: usize,
+}
+
+fn system_metadata() -> &'static SystemMetadata {
+    static SYSTEM_METADATA: OnceLock<SystemMetadata> = OnceLock::new();
+    SYSTEM_METADATA.get_or_init(|| {
+        let (git_commit, git_dirty) = std::env::current_dir()
+            .ok()
+            .and_then(|dir| find_git_dir(&dir))
+            .map(|(work_dir, git_dir)| {
+                (read_git_commit(&git_dir), is_git_dirty(&work_dir, &git_dir))
+            })
+            .unwrap_or_default();
+        SystemMetadata {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_secs())
+                .unwrap_or_default(),
+            git_commit,
+            git_dirty,
+            rustc_version: std::env::var("RUSTC_VERSION")
+                .ok()
+                .or_else(|| option_env!("BINGGAN_RUSTC_VERSION").map(str::to_string)),
+            cpu_model: cpu_model(),
+            kernel: kernel_version(),
+            num_cores: std::thread::available_parallelism()
+                .map(|cores| cores.get())
+                .unwrap_or(1),
+        }
+    })
+}
+
+impl RunMetadata {
+    /// Collects the metadata of the current run with the given config.
+    pub(crate) fn collect(config: &Config) -> Self {
+        let system = system_metadata().clone();
+        RunMetadata {
+            timestamp: system.timestamp,
+            git_commit: system.git_commit,
+            git_dirty: system.git_dirty,
+            rustc_version: system.rustc_version,
+            cpu_model: system.cpu_model,
+            kernel: system.kernel,
+            num_cores: system.num_cores,
+            features: enabled_features(),
+            config: config_to_map(config),
+        }
+    }
+
+    /// Returns the fields which differ from `old` as `(name, old value, new value)`.
+    /// The timestamp and the filter are ignored, since they don't affect the results.
+    pub fn diff(&self, old: &RunMetadata) -> Vec<(&'static str, String, String)> {
+        fn opt(value: &Option<impl ToString>) -> String {
+            value
+                .as_ref()
+                .map(|value| value.to_string())
+                .unwrap_or_else(|| "-".to_string())
+        }
+        let mut diff = Vec::new();
+        let mut check = |name: &'static str, old: String, new: String| {
+            if old != new {
+                diff.push((name, old, new));
+            }
+        };
+        check("git commit", opt(&old.git_commit), opt(&self.git_commit));
+        check("git dirty", opt(&old.git_dirty), opt(&self.git_dirty));
+        check("rustc", opt(&old.rustc_version), opt(&self.rustc_version));
+        check("cpu", opt(&old.cpu_model), opt(&self.cpu_model));
+        check("kernel", opt(&old.kernel), opt(&self.kernel));
+        check(
+            "cores",
+            old.num_cores.to_string(),
+            self.num_cores.to_string(),
+        );
+        check("features", old.features.join(","), self.features.join(","));
+        let keys: BTreeSet<&String> = old.config.keys().chain(self.config.keys()).collect();
+        for key in keys.into_iter().filter(|key| *key != "filter") {
+            let old_value = old
+                .config
+                .get(key)
+                .cloned()
+                .unwrap_or_else(|| "-".to_string());
+            let new_value = self
+                .config
+                .get(key)
+                .cloned()
+                .unwrap_or_else(|| "-".to_string());
+            if old_value != new_value {
+                diff.push((
+                    "config",
+                    format!("{}={}", key, old_value),
+                    format!("{}={}", key, new_value),
+                ));
+            }
+        }
+        diff
+    }
+}
+
+fn enabled_features() -> Vec<String> {
+    let features = [
+        ("branch_predictor", cfg!(feature = "branch_predictor")),
+        ("macros", cfg!(feature = "macros")),
+        ("real_blackbox", cfg!(feature = "real_blackbox")),
+        ("table_reporter", cfg!(feature = "table_reporter")),
+    ];
+    features
+        .iter()
+        .filter(|(_, enabled)| *enabled)
+        .map(|(name, _)| name.to_string())
+        .collect()
+}
+
+/// Returns the config values which affect the results.
+///
+/// The config is destructured exhaustively, so a new field has to be added here or explicitly
+/// ignored.
+fn config_to_map(config: &Config) -> BTreeMap<String, String> {
+    fn opt(value: Option<impl ToString>) -> String {
+        value
+            .map(|value| value.to_string())
+            .unwrap_or_else(|| "-".to_string())
+    }
+    let Config {
+        interleave,
+        filter,
+        verbose: _,
+        num_iter_bench,
+        num_iter_group: _,
+        adjust_for_single_threaded_cpu_scheduling,
+        subtract_timer_overhead,
+        preflight_check: _,
+        max_load_avg: _,
+        wait_for_low_load: _,
+        save_baseline: _,
+        baseline: _,
+        trend: _,
+        store_raw_samples: _,
+        output_format: _,
+        output_path: _,
+    } = config;
+    let entries = [
+        ("interleave", interleave.to_string()),
+        ("filter", opt(filter.as_ref())),
+        ("num_iter_bench", opt(*num_iter_bench)),
+        // Includes the `NUM_ITER_GROUP` environment variable
+        (
+            "num_iter_group",
+            config.get_num_iter_for_group().to_string(),
+        ),
+        (
+            "adjust_for_single_threaded_cpu_scheduling",
+            adjust_for_single_threaded_cpu_scheduling.to_string(),
+        ),
+        (
+            "subtract_timer_overhead",
+            subtract_timer_overhead.to_string(),
+        ),
+    ];
+    entries
+        .into_iter()
+        .map(|(key, value)| (key.to_string(), value))
+        .collect()
+}
+
+fn cpu_model() -> Option<String> {
+    let cpuinfo = std::fs::read_to_string("/proc/cpuinfo").ok()?;
+    cpuinfo.lines().find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        // `model name` on x86, `Model` on some ARM systems
+        if key.trim() == "model name" || key.trim() == "Model" {
+            Some(value.trim().to_string())
+        } else {
+            None
+        }
+    })
+}
+
+fn kernel_version() -> Option<String> {
+    std::fs::read_to_string("/proc/sys/kernel/osrelease")
+        .ok()
+        .map(|release| format!("{} {}", std::env::consts::OS, release.trim()))
+        .or_else(|| Some(std::env::consts::OS.to_string()))
+}
+
+/// Searches `.git` in `start` and its parents. Returns the work dir and the git dir.
+fn find_git_dir(start: &Path) -> Option<(PathBuf, PathBuf)> {
+    for dir in start.ancestors() {
+        let dot_git = dir.join(".git");
+        if dot_git.is_dir() {
+            return Some((dir.to_path_buf(), dot_git));
+        }
+        // Worktrees and submodules have a `.git` file pointing to the git dir
+        if let Ok(content) = std::fs::read_to_string(&dot_git) {
+            let git_dir = content.trim().strip_prefix("gitdir:")?.trim();
+            return Some((dir.to_path_buf(), dir.join(git_dir)));
+        }
+    }
+    None
+}
+
+/// Reads the commit of `HEAD`, following a symbolic ref.
+fn read_git_commit(git_dir: &Path) -> Option<String> {
+    let head = std::fs::read_to_string(git_dir.join("HEAD")).ok()?;
+    let head = head.trim();
+    let Some(reference) = head.strip_prefix("ref:") else {
+        return Some(head.to_string());
+    };
+    let reference = reference.trim();
+    // Worktrees store shared refs in the common dir
+    let common_dir = std::fs::read_to_string(git_dir.join("commondir"))
+        .ok()
+        .map(|common_dir| git_dir.join(common_dir.trim()));
+    let git_dirs = std::iter::once(git_dir.to_path_buf()).chain(common_dir);
+    for dir in git_dirs {
+        if let Ok(commit) = std::fs::read_to_string(dir.join(reference)) {
+            return Some(commit.trim().to_string());
+        }
+        if let Ok(packed_refs) = std::fs::read_to_string(dir.join("packed-refs")) {
+            let commit = packed_refs.lines().find_map(|line| {
+                let (commit, name) = line.split_once(' ')?;
+                (name == reference).then(|| commit.to_string())
+            });
+            if commit.is_some() {
+                return commit;
+            }
+        }
+    }
+    None
+}
+
+/// Checks whether tracked files were modified or deleted, by comparing the size and the
+/// modification time with the entries in the git index. Like `git status` without rehashing, so
+/// a touched but unchanged file is considered dirty. Untracked files are ignored.
+///
+/// Returns `None` if the index can't be read.
+fn is_git_dirty(work_dir: &Path, git_dir: &Path) -> Option<bool> {
+    let index = std::fs::read(git_dir.join("index")).ok()?;
+    let entries = parse_git_index(&index)?;
+    for entry in entries {
+        let Ok(metadata) = std::fs::symlink_metadata(work_dir.join(&entry.path)) else {
+            return Some(true);
+        };
+        if metadata.len() as u32 != entry.size {
+            return Some(true);
+        }
+        let (mtime_sec, mtime_nsec) = modified_time(&metadata)?;
+        // The nanoseconds are 0 if git was built without nanosecond support
+        if mtime_sec != entry.mtime_sec || (entry.mtime_nsec != 0 && mtime_nsec != entry.mtime_nsec)
+        {
+            return Some(true);
+        }
+    }
+    Some(false)
+}
+
+#[cfg(unix)]
+fn modified_time(metadata: &std::fs::Metadata) -> Option<(u32, u32)> {
+    use std::os::unix::fs::MetadataExt;
+    Some((metadata.mtime() as u32, metadata.mtime_nsec() as u32))
+}
+
+#[cfg(not(unix))]
+fn modified_time(metadata: &std::fs::Metadata) -> Option<(u32, u32)> {
+    let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+    Some((modified.as_secs() as u32, modified.subsec_nanos()))
+}
+
+#[derive(Debug, PartialEq)]
+struct GitIndexEntry {
+    path: String,
+    mtime_sec: u32,
+    mtime_nsec: u32,
+    size: u32,
+}
+
+/// Parses the entries of a git index in version 2 or 3. Submodules and entries with the
+/// skip-worktree flag are skipped.
+///
+/// See <https://git-scm.com/docs/index-format>
+fn parse_git_index(data: &[u8]) -> Option<Vec<GitIndexEntry>> {
+    let read_u32 = |pos: usize| -> Option<u32> {
+        Some(u32::from_be_bytes(data.get(pos..pos + 4)?.try_into().ok()?))
+    };
+    let read_u16 = |pos: usize| -> Option<u16> {
+        Some(u16::from_be_bytes(data.get(pos..pos + 2)?.try_into().ok()?))
+    };
+    if data.get(0..4)? != b"DIRC" {
+        return None;
+    }
+    let version = read_u32(4)?;
+    if version != 2 && version != 3 {
+        return None;
+    }
+    let num_entries = read_u32(8)?;
+    let mut entries = Vec::new();
+    let mut pos = 12;
+    for _ in 0..num_entries {
+        let start = pos;
+        let mtime_sec = read_u32(start + 8)?;
+        let mtime_nsec = read_u32(start + 12)?;
+        let mode = read_u32(start + 24)?;
+        let size = read_u32(start + 36)?;
+        let flags = read_u16(start + 60)?;
+        pos = start + 62;
+        let mut skip_worktree = false;
+        if flags & 0x4000 != 0 {
+            let extended_flags = read_u16(pos)?;
+            skip_worktree = extended_flags & 0x4000 != 0;
+            pos += 2;
+        }
+        let name_end = pos + data.get(pos..)?.iter().position(|byte| *byte == 0)?;
+        let path = String::from_utf8_lossy(&data[pos..name_end]).to_string();
+        // The entry is padded with 1-8 NUL bytes to a multiple of 8
+        pos = start + (name_end - start + 8) / 8 * 8;
+        let is_submodule = mode & 0o170000 == 0o160000;
+        if !is_submodule && !skip_worktree {
+            entries.push(GitIndexEntry {
+                path,
+                mtime_sec,
+                mtime_nsec,
+                size,
+            });
+        }
+    }
+    Some(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index_entry(path: &str, mtime_sec: u32, size: u32, mode: u32) -> Vec<u8> {
+        let mut entry = Vec::new();
+        for value in [0, 0, mtime_sec, 0, 0, 0, mode, 0, 0, size] {
+            entry.extend_from_slice(&u32::to_be_bytes(value));
+        }
+        entry.extend_from_slice(&[0; 20]);
+        entry.extend_from_slice(&u16::to_be_bytes(path.len() as u16));
+        entry.extend_from_slice(path.as_bytes());
+        let padding = 8 - entry.len() % 8;
+        entry.extend(std::iter::repeat_n(0, padding));
+        entry
+    }
+
+    #[test]
+    fn parse_git_index_entries() {
+        let mut index = b"DIRC".to_vec();
+        index.extend_from_slice(&u32::to_be_bytes(2));
+        index.extend_from_slice(&u32::to_be_bytes(3));
+        index.extend(index_entry("Cargo.toml", 100, 20, 0o100644));
+        index.extend(index_entry("submodule", 0, 0, 0o160000));
+        index.extend(index_entry("src/lib.rs", 200, 30, 0o100644));
+        let entries = parse_git_index(&index).unwrap();
+        assert_eq!(
+            entries,
+            vec![
+                GitIndexEntry {
+                    path: "Cargo.toml".to_string(),
+                    mtime_sec: 100,
+                    mtime_nsec: 0,
+                    size: 20
+                },
+                GitIndexEntry {
+                    path: "src/lib.rs".to_string(),
+                    mtime_sec: 200,
+                    mtime_nsec: 0,
+                    size: 30
+                },
+            ]
+        );
+        assert_eq!(parse_git_index(b"DIRC"), None);
+    }
+
+    #[test]
+    fn metadata_diff_ignores_timestamp() {
+        let old = RunMetadata::collect(&Config::default());
+        let mut new = old.clone();
+        new.timestamp += 10;
+        assert!(new.diff(&old).is_empty());
+        new.rustc_version = Some("rustc 2.0.0".to_string());
+        new.config
+            .insert("interleave".to_string(), "false".to_string());
+        let diff = new.diff(&old);
+        assert_eq!(diff.len(), 2);
+        assert_eq!(diff[0].0, "rustc");
+        assert_eq!(diff[1].2, "interleave=false");
+    }
+
+    #[test]
+    fn metadata_diff_ignores_config_without_effect_on_results() {
+        let mut config = Config::default();
+        let old = RunMetadata::collect(&config);
+        config.preflight_check = false;
+        config.wait_for_low_load = Some(std::time::Duration::from_secs(5));
+        config.output_format = crate::report::OutputFormat::Json;
+        config.store_raw_samples = true;
+        let new = RunMetadata::collect(&config);
+        assert!(new.diff(&old).is_empty());
+        config.subtract_timer_overhead = !config.subtract_timer_overhead;
+        let new = RunMetadata::collect(&config);
+        let diff = new.diff(&old);
+        assert_eq!(diff.len(), 1);
+        assert!(diff[0].2.starts_with("subtract_timer_overhead="));
+    }
+}
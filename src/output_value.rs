@@ -67,7 +67,7 @@ fn deserialize_json<T: miniserde::Deserialize>(serialized: &str) -> Option<T> {
     miniserde::json::from_str(serialized).ok()
 }
 
-fn format_percentage_delta(current: f64, old: f64) -> Option<String> {
+pub(crate) fn format_percentage_delta(current: f64, old: f64) -> Option<String> {
     if old == 0.0 || current == 0.0 || old == current || !old.is_finite() || !current.is_finite() {
         return None;
     }
@@ -7,7 +7,7 @@
 //! Any type that implements the [EventListener] trait can be added to [PluginManager].
 //!
 
-use crate::{bench::BenchResult, bench_id::BenchId};
+use crate::{NoiseFinding, bench::BenchResult, bench_id::BenchId};
 use std::any::Any;
 
 /// Events that can be emitted by the benchmark runner.
@@ -46,9 +46,29 @@ pub enum PluginEvents<'a> {
         /// This will include the results of all the benchmarks in the group.
         /// It also contains delta information of the last run if available
         results: &'a [BenchResult],
-        /// The name of the column of the output value.
+        /// The name of the column of the output value of the last registered bench.
+        /// Benches in a group may have different output types, see
+        /// [BenchResult::output_value_column_title] for the title of each bench.
         output_value_column_title: &'static str,
     },
+    /// The system check found conditions which may add noise to the results.
+    /// This is emitted before the first `GroupStart` of a runner.
+    NoiseFindings {
+        /// The findings of the system check
+        findings: &'a [NoiseFinding],
+    },
+    /// A lazily constructed input was built.
+    /// The next event is `GroupStart` of the group which uses the input, or `NoiseFindings` if
+    /// it is the first group of the runner.
+    InputBuilt {
+        /// The name of the input
+        input_name: &'a str,
+        /// The time it took to build the input in nanoseconds
+        duration: u64,
+    },
+    /// The benchmark run finished. This is emitted once when the
+    /// [BenchRunner](crate::BenchRunner) is dropped.
+    RunStop,
     /// A benchmark in a group is started. Note that a benchmark can be run multiple times for higher
     /// accuracy. BenchStart and BenchStop are not called for each iteration.
     ///
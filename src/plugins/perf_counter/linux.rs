@@ -4,6 +4,7 @@ use std::io;
 
 use crate::bench_id::BenchId;
 use crate::plugins::{EventListener, PerBenchData, PluginEvents};
+use crate::report::print_info;
 use perf_event::Counter;
 use perf_event::events::{Cache, CacheOp, CacheResult, Hardware, Software, WhichCache};
 use perf_event::{Builder, Group};
@@ -59,6 +60,10 @@ impl PerfCounter {
 pub(crate) struct PerfCounterGroup {
     group: Group,
     counters: Vec<(PerfCounter, Counter)>, // Store enum and corresponding counter
+    /// The counts at the end of the last sample
+    last_counts: Vec<u64>,
+    /// The counts of each sample, i.e. between enable and disable
+    samples: Vec<Vec<u64>>,
 }
 
 impl PerfCounterGroup {
@@ -71,9 +76,11 @@ impl PerfCounterGroup {
                 Ok(built_counter) => counters.push((*counter, built_counter)),
                 Err(e) => {
                     let warn = "Some counter combinations are incompatible".bold().red();
-                    println!(
+                    print_info!(
                         "{}. Disabling PerfCounter: {} \nError: {:?}",
-                        warn, counter, e
+                        warn,
+                        counter,
+                        e
                     );
                 }
             }
@@ -81,7 +88,12 @@ impl PerfCounterGroup {
 
         group.disable()?;
 
-        Ok(PerfCounterGroup { group, counters })
+        Ok(PerfCounterGroup {
+            last_counts: vec![0; counters.len()],
+            group,
+            counters,
+            samples: Vec::new(),
+        })
     }
 }
 
@@ -91,6 +103,33 @@ impl PerfCounterGroup {
     }
     pub fn disable(&mut self) {
         self.group.disable().unwrap();
+        let counts: Vec<u64> = self
+            .counters
+            .iter_mut()
+            .map(|(_, counter)| counter.read().unwrap_or(0))
+            .collect();
+        let sample = counts
+            .iter()
+            .zip(&self.last_counts)
+            .map(|(count, last_count)| count.saturating_sub(*last_count))
+            .collect();
+        self.samples.push(sample);
+        self.last_counts = counts;
+    }
+    /// Returns the counter values per iteration of each sample and clears the samples.
+    pub fn take_samples(&mut self, num_iter: u64) -> Vec<PerfCounterValues> {
+        let num_iter = num_iter as f64;
+        self.samples
+            .drain(..)
+            .map(|sample| PerfCounterValues {
+                values: self
+                    .counters
+                    .iter()
+                    .zip(sample)
+                    .map(|((counter_enum, _), count)| (*counter_enum, count as f64 / num_iter))
+                    .collect(),
+            })
+            .collect()
     }
     pub fn finish(&mut self, num_iter: u64) -> io::Result<PerfCounterValues> {
         let num_iter = num_iter as f64;
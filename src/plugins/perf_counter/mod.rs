@@ -96,6 +96,12 @@ impl FromStr for PerfCounter {
             .ok_or_else(|| format!("Invalid PerfCounter: {}", s))
     }
 }
+/// All performance counters, in a fixed order.
+#[cfg(feature = "table_reporter")]
+pub(crate) fn all_perf_counters() -> impl Iterator<Item = PerfCounter> {
+    MAPPINGS.iter().map(|(_, counter)| *counter)
+}
+
 /// Get the default performance counters.
 pub fn default_perf_counters() -> &'static [PerfCounter] {
     &[
@@ -141,6 +147,34 @@ fn print_counter_value<F: Fn(f64) -> f64>(
 }
 
 impl PerfCounterValues {
+    /// The counter values per iteration.
+    pub fn values(&self) -> &[(PerfCounter, f64)] {
+        &self.values
+    }
+
+    /// Aggregates the values of each counter with `aggregate`, e.g. the median over multiple
+    /// runs. The counters are taken from the first values.
+    #[cfg(feature = "cli")]
+    pub(crate) fn aggregate(all: &[&Self], aggregate: impl Fn(Vec<f64>) -> f64) -> Self {
+        let Some(first) = all.first() else {
+            return PerfCounterValues { values: Vec::new() };
+        };
+        let values = first
+            .values
+            .iter()
+            .map(|(counter, _)| {
+                let counter_values: Vec<f64> = all
+                    .iter()
+                    .flat_map(|values| &values.values)
+                    .filter(|(other, _)| other == counter)
+                    .map(|(_, value)| *value)
+                    .collect();
+                (*counter, aggregate(counter_values))
+            })
+            .collect();
+        PerfCounterValues { values }
+    }
+
     /// Method to compare two `Vec<(PerfCounter, f64)>` instances and return formatted columns
     pub fn to_columns(&self, other_values: Option<&Self>) -> Vec<String> {
         let mut result = Vec::new();
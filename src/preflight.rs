@@ -0,0 +1,259 @@
+use std::fmt;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crate::report::print_info;
+
+/// A condition of the machine, which may add noise to the benchmark results.
+///
+/// The system is checked before the first group of a runner is run, see
+/// [Config::preflight_check](crate::Config::preflight_check).
+/// The findings are reported and stored with the results.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NoiseFinding {
+    /// The CPU frequency governor is not set to `performance`.
+    CpuGovernor {
+        /// The governor, e.g. `powersave`.
+        governor: String,
+    },
+    /// Turbo boost is enabled, so the CPU frequency depends on temperature and load.
+    TurboEnabled,
+    /// The 1 minute load average is above the threshold.
+    HighLoad {
+        /// The 1 minute load average.
+        load_avg: f64,
+        /// The threshold, see [Config::max_load_avg](crate::Config::max_load_avg).
+        max_load_avg: f64,
+    },
+    /// `perf_event_paranoid` is too restrictive for the
+    /// [PerfCounterPlugin](crate::plugins::PerfCounterPlugin).
+    PerfEventParanoid {
+        /// The value of `/proc/sys/kernel/perf_event_paranoid`.
+        level: i32,
+    },
+}
+
+impl fmt::Display for NoiseFinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NoiseFinding::CpuGovernor { governor } => write!(
+                f,
+                "CPU frequency governor is `{}` instead of `performance` (`sudo cpupower frequency-set -g performance`)",
+                governor
+            ),
+            NoiseFinding::TurboEnabled => write!(
+                f,
+                "Turbo boost is enabled, the CPU frequency depends on temperature and load"
+            ),
+            NoiseFinding::HighLoad {
+                load_avg,
+                max_load_avg,
+            } => write!(
+                f,
+                "Load average is {:.2}, which is above {:.2}. Other processes may interfere",
+                load_avg, max_load_avg
+            ),
+            NoiseFinding::PerfEventParanoid { level } => write!(
+                f,
+                "perf_event_paranoid is {}, which blocks the PerfCounterPlugin (`sudo sysctl kernel.perf_event_paranoid=2`)",
+                level
+            ),
+        }
+    }
+}
+
+/// Checks the system below `root` for noisy conditions. `root` is `/` except in tests.
+///
+/// Files that don't exist, e.g. on other platforms or in containers, are skipped.
+pub(crate) fn check_system(
+    root: &Path,
+    max_load_avg: f64,
+    uses_perf_counter: bool,
+) -> Vec<NoiseFinding> {
+    let mut findings = Vec::new();
+
+    let mut governors: Vec<String> = Vec::new();
+    if let Ok(entries) = std::fs::read_dir(root.join("sys/devices/system/cpu")) {
+        let mut cpu_dirs: Vec<_> = entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| {
+                let name = entry.file_name();
+                let name = name.to_string_lossy();
+                name.starts_with("cpu") && name[3..].chars().all(|c| c.is_ascii_digit())
+            })
+            .map(|entry| entry.path())
+            .collect();
+        cpu_dirs.sort();
+        for cpu_dir in cpu_dirs {
+            if let Some(governor) = read_trimmed(&cpu_dir.join("cpufreq/scaling_governor")) {
+                if governor != "performance" && !governors.contains(&governor) {
+                    governors.push(governor);
+                }
+            }
+        }
+    }
+    findings.extend(
+        governors
+            .into_iter()
+            .map(|governor| NoiseFinding::CpuGovernor { governor }),
+    );
+
+    // intel_pstate reports `no_turbo`, acpi-cpufreq and amd-pstate report `boost`
+    let no_turbo = read_trimmed(&root.join("sys/devices/system/cpu/intel_pstate/no_turbo"));
+    let boost = read_trimmed(&root.join("sys/devices/system/cpu/cpufreq/boost"));
+    if no_turbo.as_deref() == Some("0") || (no_turbo.is_none() && boost.as_deref() == Some("1")) {
+        findings.push(NoiseFinding::TurboEnabled);
+    }
+
+    if let Some(load_avg) = read_load_avg(root) {
+        if load_avg > max_load_avg {
+            findings.push(NoiseFinding::HighLoad {
+                load_avg,
+                max_load_avg,
+            });
+        }
+    }
+
+    if uses_perf_counter {
+        if let Some(level) = read_trimmed(&root.join("proc/sys/kernel/perf_event_paranoid"))
+            .and_then(|level| level.parse::<i32>().ok())
+        {
+            // The perf counters exclude the kernel, which is allowed up to level 2
+            if level > 2 {
+                findings.push(NoiseFinding::PerfEventParanoid { level });
+            }
+        }
+    }
+
+    findings
+}
+
+/// Waits until the 1 minute load average drops to `max_load_avg` or the timeout is reached.
+///
+/// Returns true if the load dropped.
+pub(crate) fn wait_for_low_load(root: &Path, max_load_avg: f64, timeout: Duration) -> bool {
+    let start = Instant::now();
+    let mut printed = false;
+    loop {
+        let Some(load_avg) = read_load_avg(root) else {
+            return true;
+        };
+        if load_avg <= max_load_avg {
+            return true;
+        }
+        if start.elapsed() >= timeout {
+            return false;
+        }
+        if !printed {
+            print_info!(
+                "Waiting up to {}s for load average {:.2} to drop below {:.2}",
+                timeout.as_secs(),
+                load_avg,
+                max_load_avg
+            );
+            printed = true;
+        }
+        std::thread::sleep(Duration::from_secs(1).min(timeout.saturating_sub(start.elapsed())));
+    }
+}
+
+/// The default threshold for the load average: Half of the cores, but at least 1.
+pub(crate) fn default_max_load_avg() -> f64 {
+    let num_cores = std::thread::available_parallelism()
+        .map(|cores| cores.get())
+        .unwrap_or(1);
+    (num_cores as f64 / 2.0).max(1.0)
+}
+
+fn read_load_avg(root: &Path) -> Option<f64> {
+    read_trimmed(&root.join("proc/loadavg"))?
+        .split_whitespace()
+        .next()?
+        .parse()
+        .ok()
+}
+
+fn read_trimmed(path: &Path) -> Option<String> {
+    std::fs::read_to_string(path)
+        .ok()
+        .map(|content| content.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn fake_root(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("binggan_preflight_{}", name));
+        let _ = std::fs::remove_dir_all(&root);
+        for (path, content) in files {
+            let path = root.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        }
+        root
+    }
+
+    #[test]
+    fn noisy_system() {
+        let root = fake_root(
+            "noisy",
+            &[
+                (
+                    "sys/devices/system/cpu/cpu0/cpufreq/scaling_governor",
+                    "powersave\n",
+                ),
+                (
+                    "sys/devices/system/cpu/cpu1/cpufreq/scaling_governor",
+                    "powersave\n",
+                ),
+                (
+                    "sys/devices/system/cpu/cpu2/cpufreq/scaling_governor",
+                    "performance\n",
+                ),
+                ("sys/devices/system/cpu/intel_pstate/no_turbo", "0\n"),
+                ("proc/loadavg", "3.50 2.00 1.00 2/300 1234\n"),
+                ("proc/sys/kernel/perf_event_paranoid", "3\n"),
+            ],
+        );
+        assert_eq!(
+            check_system(&root, 1.0, true),
+            vec![
+                NoiseFinding::CpuGovernor {
+                    governor: "powersave".to_string()
+                },
+                NoiseFinding::TurboEnabled,
+                NoiseFinding::HighLoad {
+                    load_avg: 3.5,
+                    max_load_avg: 1.0
+                },
+                NoiseFinding::PerfEventParanoid { level: 3 },
+            ]
+        );
+        // perf_event_paranoid is only relevant with the PerfCounterPlugin
+        assert_eq!(check_system(&root, 4.0, false).len(), 2);
+        assert!(!wait_for_low_load(&root, 1.0, Duration::ZERO));
+    }
+
+    #[test]
+    fn quiet_system() {
+        let root = fake_root(
+            "quiet",
+            &[
+                (
+                    "sys/devices/system/cpu/cpu0/cpufreq/scaling_governor",
+                    "performance\n",
+                ),
+                ("sys/devices/system/cpu/cpufreq/boost", "0\n"),
+                ("proc/loadavg", "0.10 0.20 0.30 1/300 1234\n"),
+                ("proc/sys/kernel/perf_event_paranoid", "2\n"),
+            ],
+        );
+        assert_eq!(check_system(&root, 1.0, true), vec![]);
+        assert!(wait_for_low_load(&root, 1.0, Duration::ZERO));
+        // Missing files are skipped
+        assert_eq!(check_system(&root.join("missing"), 1.0, true), vec![]);
+    }
+}
@@ -0,0 +1,369 @@
+use std::any::Any;
+use std::path::{Path, PathBuf};
+
+use miniserde::Serialize;
+use yansi::Paint;
+
+use super::print_info;
+use crate::{
+    bench::BenchResult,
+    plugins::{EventListener, PluginEvents},
+};
+
+/// The plugin name of the [CriterionWriter]. It is added next to the default reporter.
+pub const CRITERION_WRITER_PLUGIN_NAME: &str = "criterion_writer";
+
+/// The number of bootstrap resamples for the confidence intervals.
+const NUM_RESAMPLES: usize = 1_000;
+const CONFIDENCE_LEVEL: f64 = 0.95;
+
+/// The CriterionWriter mirrors the results into the directory layout of
+/// [criterion](https://github.com/bheisler/criterion.rs), so tools like `critcmp` can read them.
+///
+/// For each bench `<id>/new/benchmark.json` and `<id>/new/estimates.json` are written to
+/// `target/criterion`. Like in criterion, the previous `new` directory is moved to `base`.
+/// `estimates.json` is skipped for benches without samples.
+/// The estimates contain the mean, median, standard deviation and median absolute deviation of
+/// the time per iteration in nanoseconds, with bootstrapped 95% confidence intervals.
+///
+/// The runner, group and bench name of the [BenchId](crate::BenchId) map to the group,
+/// function and value of criterion. Missing names are skipped, e.g. a bench of a group
+/// without runner name maps to `<group>/<bench>`.
+///
+/// ## Usage Example
+/// ```rust
+/// use binggan::*;
+/// use binggan::report::CriterionWriter;
+///
+/// let mut runner = BenchRunner::new();
+/// runner.add_plugin(CriterionWriter::new());
+/// ```
+#[derive(Clone)]
+pub struct CriterionWriter {
+    output_directory: PathBuf,
+    baseline: Option<String>,
+}
+
+impl Default for CriterionWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CriterionWriter {
+    /// Creates a CriterionWriter, which writes to `criterion` in the cargo target directory.
+    pub fn new() -> Self {
+        let target_directory = std::env::var_os("CARGO_TARGET_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from("target"));
+        Self::with_directory(target_directory.join("criterion"))
+    }
+
+    /// Creates a CriterionWriter, which writes to the given directory.
+    pub fn with_directory<P: Into<PathBuf>>(output_directory: P) -> Self {
+        CriterionWriter {
+            output_directory: output_directory.into(),
+            baseline: None,
+        }
+    }
+
+    /// Writes the results as the named baseline instead of `new`, like criterion's
+    /// `--save-baseline <name>`.
+    pub fn save_baseline<S: Into<String>>(mut self, name: S) -> Self {
+        self.baseline = Some(name.into());
+        self
+    }
+
+    fn write_result(&self, result: &BenchResult) -> std::io::Result<()> {
+        let benchmark = CriterionBenchmark::from_result(result);
+        let bench_directory = self.output_directory.join(&benchmark.directory_name);
+        let target = match &self.baseline {
+            Some(baseline) => bench_directory.join(baseline),
+            None => {
+                rotate_new_to_base(&bench_directory)?;
+                bench_directory.join("new")
+            }
+        };
+        let samples: Vec<f64> = result
+            .raw_samples
+            .durations_ns
+            .iter()
+            .map(|duration| *duration as f64)
+            .collect();
+        std::fs::create_dir_all(&target)?;
+        std::fs::write(
+            target.join("benchmark.json"),
+            miniserde::json::to_string(&benchmark),
+        )?;
+        match Estimates::from_samples(&samples) {
+            Some(estimates) => std::fs::write(
+                target.join("estimates.json"),
+                miniserde::json::to_string(&estimates),
+            ),
+            None => Ok(()),
+        }
+    }
+}
+
+fn rotate_new_to_base(bench_directory: &Path) -> std::io::Result<()> {
+    let new = bench_directory.join("new");
+    if !new.exists() {
+        return Ok(());
+    }
+    let base = bench_directory.join("base");
+    if base.exists() {
+        std::fs::remove_dir_all(&base)?;
+    }
+    std::fs::rename(new, base)
+}
+
+impl EventListener for CriterionWriter {
+    fn as_any(&mut self) -> &mut dyn Any {
+        self
+    }
+    fn name(&self) -> &'static str {
+        CRITERION_WRITER_PLUGIN_NAME
+    }
+    fn on_event(&mut self, event: PluginEvents) {
+        if let PluginEvents::GroupStop { results, .. } = event {
+            for result in results {
+                if let Err(err) = self.write_result(result) {
+                    print_info!(
+                        "{} Could not write the criterion results of {}: {}",
+                        "Warning:".yellow().bold(),
+                        result.bench_id,
+                        err
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// The `benchmark.json` of criterion.
+#[derive(Serialize, Debug)]
+struct CriterionBenchmark {
+    group_id: String,
+    function_id: Option<String>,
+    value_str: Option<String>,
+    throughput: Option<Throughput>,
+    full_id: String,
+    directory_name: String,
+    title: String,
+}
+
+#[derive(Serialize, Debug)]
+struct Throughput {
+    #[serde(rename = "Bytes")]
+    bytes: u64,
+}
+
+impl CriterionBenchmark {
+    fn from_result(result: &BenchResult) -> Self {
+        let bench_id = &result.bench_id;
+        let mut ids = [
+            bench_id.runner_name.as_deref(),
+            bench_id.group_name.as_deref(),
+            Some(bench_id.bench_name.as_str()),
+        ]
+        .into_iter()
+        .flatten()
+        .map(str::to_string);
+        let group_id = ids.next().unwrap_or_default();
+        let function_id = ids.next();
+        let value_str = ids.next();
+        let parts: Vec<&String> = std::iter::once(&group_id)
+            .chain(function_id.as_ref())
+            .chain(value_str.as_ref())
+            .collect();
+        let full_id = parts
+            .iter()
+            .map(|part| part.as_str())
+            .collect::<Vec<_>>()
+            .join("/");
+        let directory_name = parts
+            .iter()
+            .map(|part| make_filename_safe(part))
+            .collect::<Vec<_>>()
+            .join("/");
+        CriterionBenchmark {
+            group_id,
+            function_id,
+            value_str,
+            throughput: result.input_size_in_bytes.map(|bytes| Throughput {
+                bytes: bytes as u64,
+            }),
+            title: full_id.clone(),
+            full_id,
+            directory_name,
+        }
+    }
+}
+
+/// Replaces characters which are not allowed in file names, like criterion does.
+fn make_filename_safe(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            '?' | '"' | '/' | '\\' | '*' | '<' | '>' | ':' | '|' | '^' => '_',
+            c => c,
+        })
+        .collect()
+}
+
+/// The `estimates.json` of criterion.
+#[derive(Serialize, Debug)]
+struct Estimates {
+    mean: Estimate,
+    median: Estimate,
+    median_abs_dev: Estimate,
+    slope: Option<Estimate>,
+    std_dev: Estimate,
+}
+
+#[derive(Serialize, Debug)]
+struct Estimate {
+    confidence_interval: ConfidenceInterval,
+    point_estimate: f64,
+    standard_error: f64,
+}
+
+#[derive(Serialize, Debug)]
+struct ConfidenceInterval {
+    confidence_level: f64,
+    lower_bound: f64,
+    upper_bound: f64,
+}
+
+impl Estimates {
+    /// Returns None without samples, since nothing can be estimated.
+    fn from_samples(samples: &[f64]) -> Option<Self> {
+        if samples.is_empty() {
+            return None;
+        }
+        // A fixed seed keeps the intervals reproducible for the same samples
+        let mut rng = XorShift(0x2545_f491_4f6c_dd1d);
+        Some(Estimates {
+            mean: bootstrap(samples, mean, &mut rng),
+            median: bootstrap(samples, median, &mut rng),
+            median_abs_dev: bootstrap(samples, median_abs_dev, &mut rng),
+            slope: None,
+            std_dev: bootstrap(samples, std_dev, &mut rng),
+        })
+    }
+}
+
+/// Estimates the statistic and its confidence interval by resampling with replacement.
+fn bootstrap(samples: &[f64], statistic: fn(&[f64]) -> f64, rng: &mut XorShift) -> Estimate {
+    let point_estimate = statistic(samples);
+    let mut resample = vec![0.0; samples.len()];
+    let mut distribution: Vec<f64> = (0..NUM_RESAMPLES)
+        .map(|_| {
+            for value in resample.iter_mut() {
+                *value = samples[rng.next() as usize % samples.len()];
+            }
+            statistic(&resample)
+        })
+        .collect();
+    distribution.sort_by(|a, b| a.total_cmp(b));
+    let alpha = (1.0 - CONFIDENCE_LEVEL) / 2.0;
+    Estimate {
+        confidence_interval: ConfidenceInterval {
+            confidence_level: CONFIDENCE_LEVEL,
+            lower_bound: percentile(&distribution, alpha),
+            upper_bound: percentile(&distribution, 1.0 - alpha),
+        },
+        point_estimate,
+        standard_error: std_dev(&distribution),
+    }
+}
+
+fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+fn median(values: &[f64]) -> f64 {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    percentile(&sorted, 0.5)
+}
+
+fn median_abs_dev(values: &[f64]) -> f64 {
+    let median = median(values);
+    let deviations: Vec<f64> = values.iter().map(|value| (value - median).abs()).collect();
+    // Scaled to be a consistent estimator of the standard deviation, like in criterion
+    self::median(&deviations) * 1.4826
+}
+
+fn std_dev(values: &[f64]) -> f64 {
+    if values.len() < 2 {
+        return 0.0;
+    }
+    let mean = mean(values);
+    let variance = values
+        .iter()
+        .map(|value| (value - mean).powi(2))
+        .sum::<f64>()
+        / (values.len() - 1) as f64;
+    variance.sqrt()
+}
+
+/// Linear interpolated percentile of sorted values, `p` in 0..=1. Returns 0 without values.
+pub(super) fn percentile(sorted: &[f64], p: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = p * (sorted.len() - 1) as f64;
+    let lower = rank.floor() as usize;
+    let upper = rank.ceil() as usize;
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as f64)
+}
+
+/// A small, seedable pseudo random number generator for the resampling.
+struct XorShift(u64);
+
+impl XorShift {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn estimates_test() {
+        assert!(Estimates::from_samples(&[]).is_none());
+        let estimates = Estimates::from_samples(&[5.0; 10]).unwrap();
+        assert_eq!(estimates.mean.point_estimate, 5.0);
+        assert_eq!(estimates.mean.confidence_interval.lower_bound, 5.0);
+        assert_eq!(estimates.mean.confidence_interval.upper_bound, 5.0);
+        assert_eq!(estimates.std_dev.point_estimate, 0.0);
+
+        let samples: Vec<f64> = (1..=100).map(|value| value as f64).collect();
+        let estimates = Estimates::from_samples(&samples).unwrap();
+        assert_eq!(estimates.mean.point_estimate, 50.5);
+        assert_eq!(estimates.median.point_estimate, 50.5);
+        let interval = &estimates.mean.confidence_interval;
+        assert!(interval.lower_bound < 50.5 && 50.5 < interval.upper_bound);
+        assert!(interval.lower_bound > 40.0 && interval.upper_bound < 60.0);
+        assert!((estimates.std_dev.point_estimate - 29.01).abs() < 0.01);
+    }
+
+    #[test]
+    fn percentile_test() {
+        assert_eq!(percentile(&[1.0, 2.0, 3.0, 4.0], 0.5), 2.5);
+        assert_eq!(percentile(&[1.0, 2.0, 3.0], 0.0), 1.0);
+        assert_eq!(percentile(&[1.0, 2.0, 3.0], 1.0), 3.0);
+        assert_eq!(percentile(&[], 0.5), 0.0);
+    }
+
+    #[test]
+    fn filename_safe_test() {
+        assert_eq!(make_filename_safe("a/b:c"), "a_b_c");
+        assert_eq!(make_filename_safe("fib 10"), "fib 10");
+    }
+}
@@ -0,0 +1,251 @@
+use std::any::Any;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use yansi::Paint;
+
+use super::{SharedOutputs, print_info, with_shared_output};
+use crate::{
+    bench::BenchResult,
+    plugins::{EventListener, PerfCounter, PluginEvents},
+    stats::{BenchStats, compute_percentage_diff},
+};
+
+/// The plugin name of the [CsvReporter]. It is added next to the default reporter.
+pub const CSV_REPORTER_PLUGIN_NAME: &str = "csv_reporter";
+
+type StatsAccessor = fn(&BenchStats) -> u64;
+
+/// The names and accessors of the [BenchStats] columns.
+const STATS_COLUMNS: &[(&str, StatsAccessor)] = &[
+    ("min_ns", |stats| stats.min_ns),
+    ("max_ns", |stats| stats.max_ns),
+    ("average_ns", |stats| stats.average_ns),
+    ("median_ns", |stats| stats.median_ns),
+    ("avg_memory", |stats| stats.avg_memory as u64),
+];
+
+/// The CsvReporter appends a row per bench to a CSV file on every finished group, e.g. to
+/// analyze the results in a spreadsheet.
+///
+/// The file is created on the first group of the process. The groups of other
+/// [BenchRunner](crate::BenchRunner)s writing to the same path are appended. The file contains
+/// the columns `runner`, `group`, `bench`, the [BenchStats] fields, the values of the previous
+/// run prefixed with `old_`, the deltas in percent prefixed with `delta_`, one column per enabled
+/// perf counter, `input_size_in_bytes` and `output_value`. The perf counter columns are taken
+/// from the first group.
+///
+/// ## Usage Example
+/// ```rust
+/// use binggan::*;
+/// use binggan::report::CsvReporter;
+///
+/// let mut runner = BenchRunner::new();
+/// runner.add_plugin(CsvReporter::new("target/binggan/results.csv"));
+/// ```
+#[derive(Clone)]
+pub struct CsvReporter {
+    output_path: PathBuf,
+}
+
+/// The perf counter columns of each file, set when the header is written. They are shared by all
+/// CsvReporters in the process, see [SharedOutputs], so other runners append to the file.
+static PERF_COUNTER_COLUMNS: SharedOutputs<Option<Vec<PerfCounter>>> = Mutex::new(Vec::new());
+
+impl CsvReporter {
+    /// Creates a CsvReporter, which writes to the file at `path`. An existing file is
+    /// overwritten.
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        CsvReporter {
+            output_path: path.into(),
+        }
+    }
+
+    fn add_group(&mut self, results: &[BenchResult]) -> std::io::Result<()> {
+        let (is_new_file, perf_counters) =
+            with_shared_output(&PERF_COUNTER_COLUMNS, Some(&self.output_path), |columns| {
+                let is_new_file = columns.is_none();
+                let perf_counters = columns.get_or_insert_with(|| {
+                    results
+                        .iter()
+                        .find_map(|result| result.perf_counter.as_ref())
+                        .map(|perf_counter| {
+                            perf_counter
+                                .values()
+                                .iter()
+                                .map(|(counter, _)| *counter)
+                                .collect()
+                        })
+                        .unwrap_or_default()
+                });
+                (is_new_file, perf_counters.clone())
+            });
+        let mut content = String::new();
+        if is_new_file {
+            push_row(&mut content, header(&perf_counters));
+        }
+        for result in results {
+            push_row(&mut content, row(result, &perf_counters));
+        }
+
+        if let Some(parent) = self.output_path.parent() {
+            if !parent.as_os_str().is_empty() {
+                std::fs::create_dir_all(parent)?;
+            }
+        }
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(!is_new_file)
+            .truncate(is_new_file)
+            .open(&self.output_path)?;
+        file.write_all(content.as_bytes())
+    }
+}
+
+impl EventListener for CsvReporter {
+    fn as_any(&mut self) -> &mut dyn Any {
+        self
+    }
+    fn name(&self) -> &'static str {
+        CSV_REPORTER_PLUGIN_NAME
+    }
+    fn on_event(&mut self, event: PluginEvents) {
+        if let PluginEvents::GroupStop { results, .. } = event {
+            if let Err(err) = self.add_group(results) {
+                print_info!(
+                    "{} Could not write the results to {}: {}",
+                    "Warning:".yellow().bold(),
+                    self.output_path.display(),
+                    err
+                );
+            }
+        }
+    }
+}
+
+fn header(perf_counters: &[PerfCounter]) -> Vec<String> {
+    let mut columns: Vec<String> = vec!["runner".into(), "group".into(), "bench".into()];
+    for prefix in ["", "old_", "delta_"] {
+        for (name, _) in STATS_COLUMNS {
+            let suffix = if prefix == "delta_" { "_pct" } else { "" };
+            columns.push(format!("{}{}{}", prefix, name, suffix));
+        }
+    }
+    columns.extend(perf_counters.iter().map(|counter| counter.to_string()));
+    columns.push("input_size_in_bytes".into());
+    columns.push("output_value".into());
+    columns
+}
+
+fn row(result: &BenchResult, perf_counters: &[PerfCounter]) -> Vec<String> {
+    let bench_id = &result.bench_id;
+    let mut columns = vec![
+        bench_id.runner_name.clone().unwrap_or_default(),
+        bench_id.group_name.clone().unwrap_or_default(),
+        bench_id.bench_name.clone(),
+    ];
+    for (_, get) in STATS_COLUMNS {
+        columns.push(get(&result.stats).to_string());
+    }
+    for (_, get) in STATS_COLUMNS {
+        columns.push(opt(result.old_stats.as_ref().map(get)));
+    }
+    for (_, get) in STATS_COLUMNS {
+        let delta = result
+            .old_stats
+            .as_ref()
+            .map(get)
+            .filter(|old| *old != 0)
+            .map(|old| {
+                format!(
+                    "{:.2}",
+                    compute_percentage_diff(get(&result.stats) as f64, old as f64)
+                )
+            });
+        columns.push(delta.unwrap_or_default());
+    }
+    for counter in perf_counters {
+        let value = result.perf_counter.as_ref().and_then(|perf_counter| {
+            perf_counter
+                .values()
+                .iter()
+                .find(|(other, _)| other == counter)
+                .map(|(_, value)| *value)
+        });
+        columns.push(opt(value));
+    }
+    columns.push(opt(result.input_size_in_bytes));
+    columns.push(result.output_value.clone().unwrap_or_default());
+    columns
+}
+
+fn opt<T: ToString>(value: Option<T>) -> String {
+    value.map(|value| value.to_string()).unwrap_or_default()
+}
+
+fn push_row(content: &mut String, columns: Vec<String>) {
+    let columns: Vec<String> = columns.iter().map(|column| escape(column)).collect();
+    content.push_str(&columns.join(","));
+    content.push('\n');
+}
+
+/// Quotes the field if it contains a separator, quote or line break.
+fn escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(bench_name: &str, old_stats: Option<BenchStats>) -> BenchResult {
+        BenchResult {
+            old_stats,
+            output_value: Some("1,000".to_string()),
+            ..BenchResult::for_test(bench_name, 150)
+        }
+    }
+
+    #[test]
+    fn escape_test() {
+        assert_eq!(escape("plain"), "plain");
+        assert_eq!(escape("1,000"), "\"1,000\"");
+        assert_eq!(escape("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+
+    #[test]
+    fn write_csv() {
+        let dir = std::env::temp_dir().join("binggan_csv_reporter");
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = dir.join("results.csv");
+        CsvReporter::new(&path)
+            .add_group(&[result("a", Some(BenchResult::for_test("a", 100).stats))])
+            .unwrap();
+        // Another runner appends to the file
+        CsvReporter::new(&path)
+            .add_group(&[result("b", None)])
+            .unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(
+            lines[0],
+            "runner,group,bench,min_ns,max_ns,average_ns,median_ns,avg_memory,\
+             old_min_ns,old_max_ns,old_average_ns,old_median_ns,old_avg_memory,\
+             delta_min_ns_pct,delta_max_ns_pct,delta_average_ns_pct,delta_median_ns_pct,\
+             delta_avg_memory_pct,input_size_in_bytes,output_value"
+        );
+        assert_eq!(
+            lines[1],
+            ",group,a,75,300,150,150,0,50,200,100,100,0,50.00,50.00,50.00,50.00,,,\"1,000\""
+        );
+        assert_eq!(lines[2], ",group,b,75,300,150,150,0,,,,,,,,,,,,\"1,000\"");
+    }
+}
@@ -0,0 +1,689 @@
+use std::any::Any;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use yansi::Paint;
+
+use super::{
+    SharedOutputs,
+    criterion_writer::percentile,
+    format::{bytes_to_string, format_duration, format_duration_or_throughput},
+    print_info, with_shared_output,
+};
+use crate::{
+    RunMetadata,
+    bench::BenchResult,
+    history::{BenchTrend, HistoryEntry},
+    plugins::{EventListener, PluginEvents},
+    stats::compute_percentage_diff,
+    write_results::get_output_directory,
+};
+
+/// The plugin name of the [HtmlReporter]. It is added next to the default reporter.
+pub const HTML_REPORTER_PLUGIN_NAME: &str = "html_reporter";
+
+/// The number of runs shown in the history charts.
+const NUM_HISTORY_RUNS: usize = 50;
+
+const CHART_WIDTH: f64 = 800.0;
+const LABEL_WIDTH: f64 = 200.0;
+const VALUE_LABEL_WIDTH: f64 = 130.0;
+const ROW_HEIGHT: f64 = 26.0;
+
+/// The HtmlReporter generates a static HTML report with SVG charts, without JavaScript or
+/// network assets.
+///
+/// The report is written to `target/binggan/report/` by default. `index.html` links a page per
+/// group, which contains a table of the results and charts of
+/// - the median time per bench,
+/// - the throughput per bench, if an input size is set,
+/// - box plots of the samples, i.e. min, quartiles, median and max,
+/// - the median over the last runs, if the bench has a history.
+///
+/// The pages are written after each group. The report contains the groups of all
+/// [BenchRunner](crate::BenchRunner)s of the process, which write to the same directory. Pages
+/// of previous runs are removed when the first group of the process is written, so the directory
+/// should not contain other `.html` files.
+///
+/// ## Usage Example
+/// ```rust
+/// use binggan::*;
+/// use binggan::report::HtmlReporter;
+///
+/// let mut runner = BenchRunner::new();
+/// runner.add_plugin(HtmlReporter::new());
+/// ```
+#[derive(Clone)]
+pub struct HtmlReporter {
+    output_directory: PathBuf,
+}
+
+/// The report of a directory.
+#[derive(Default)]
+struct HtmlReport {
+    /// Whether the pages of previous runs were removed.
+    removed_old_pages: bool,
+    metadata: Option<RunMetadata>,
+    noise_findings: Vec<String>,
+    /// The title and file name of the pages of the run so far.
+    pages: Vec<(String, String)>,
+}
+
+/// The reports of all HtmlReporters in the process, see [SharedOutputs].
+static REPORTS: SharedOutputs<HtmlReport> = Mutex::new(Vec::new());
+
+impl Default for HtmlReporter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HtmlReporter {
+    /// Creates a HtmlReporter, which writes to `report` in the binggan output directory.
+    pub fn new() -> Self {
+        Self::with_directory(get_output_directory().join("report"))
+    }
+
+    /// Creates a HtmlReporter, which writes to the given directory.
+    pub fn with_directory<P: Into<PathBuf>>(output_directory: P) -> Self {
+        HtmlReporter {
+            output_directory: output_directory.into(),
+        }
+    }
+
+    fn with_report<R>(&self, f: impl FnOnce(&mut HtmlReport) -> R) -> R {
+        with_shared_output(&REPORTS, Some(&self.output_directory), f)
+    }
+
+    fn add_group(
+        &mut self,
+        runner_name: Option<&str>,
+        group_name: Option<&str>,
+        results: &[BenchResult],
+    ) -> std::io::Result<()> {
+        let output_directory = &self.output_directory;
+        self.with_report(|report| {
+            if !report.removed_old_pages {
+                remove_pages(output_directory)?;
+                report.removed_old_pages = true;
+            }
+            if report.metadata.is_none() {
+                report.metadata = results.iter().find_map(|result| result.metadata.clone());
+            }
+            let mut title = [runner_name, group_name]
+                .into_iter()
+                .flatten()
+                .collect::<Vec<_>>()
+                .join(" / ");
+            if title.is_empty() {
+                title = format!("Group {}", report.pages.len() + 1);
+            }
+            let file_name = format!("{:02}-{}.html", report.pages.len() + 1, slug(&title));
+            let trends: Vec<BenchTrend> = results
+                .iter()
+                .map(|result| BenchTrend::load(&result.bench_id, NUM_HISTORY_RUNS))
+                .collect();
+            let content = render_group(&title, results, &trends);
+            std::fs::write(output_directory.join(&file_name), page(&title, &content))?;
+            report.pages.push((title, file_name));
+            std::fs::write(
+                output_directory.join("index.html"),
+                page("Benchmark Report", &report.render_index()),
+            )
+        })
+    }
+}
+
+/// Removes the pages of previous runs.
+fn remove_pages(output_directory: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(output_directory)?;
+    for entry in std::fs::read_dir(output_directory)? {
+        let path = entry?.path();
+        if path.is_file() && path.extension().is_some_and(|ext| ext == "html") {
+            std::fs::remove_file(path)?;
+        }
+    }
+    Ok(())
+}
+
+impl HtmlReport {
+    fn render_index(&self) -> String {
+        let mut out = String::from("<h1>Benchmark Report</h1>\n");
+        if let Some(metadata) = &self.metadata {
+            out.push_str("<table class=\"metadata\">\n");
+            let rows = [
+                ("Git commit", metadata.git_commit.clone()),
+                ("rustc", metadata.rustc_version.clone()),
+                ("CPU", metadata.cpu_model.clone()),
+                ("Kernel", metadata.kernel.clone()),
+                ("Cores", Some(metadata.num_cores.to_string())),
+            ];
+            for (name, value) in rows {
+                if let Some(value) = value {
+                    out.push_str(&format!(
+                        "<tr><th>{}</th><td>{}</td></tr>\n",
+                        name,
+                        escape(&value)
+                    ));
+                }
+            }
+            out.push_str("</table>\n");
+        }
+        for finding in &self.noise_findings {
+            out.push_str(&format!(
+                "<p class=\"warning\">Warning: {}</p>\n",
+                escape(finding)
+            ));
+        }
+        out.push_str("<ul>\n");
+        for (title, file_name) in &self.pages {
+            out.push_str(&format!(
+                "<li><a href=\"{}\">{}</a></li>\n",
+                escape(file_name),
+                escape(title)
+            ));
+        }
+        out.push_str("</ul>\n");
+        out
+    }
+}
+
+impl EventListener for HtmlReporter {
+    fn as_any(&mut self) -> &mut dyn Any {
+        self
+    }
+    fn name(&self) -> &'static str {
+        HTML_REPORTER_PLUGIN_NAME
+    }
+    fn on_event(&mut self, event: PluginEvents) {
+        match event {
+            PluginEvents::NoiseFindings { findings } => {
+                self.with_report(|report| {
+                    report.noise_findings =
+                        findings.iter().map(|finding| finding.to_string()).collect();
+                });
+            }
+            PluginEvents::GroupStop {
+                runner_name,
+                group_name,
+                results,
+                output_value_column_title: _,
+            } => {
+                if let Err(err) = self.add_group(runner_name, group_name, results) {
+                    print_info!(
+                        "{} Could not write the HTML report to {}: {}",
+                        "Warning:".yellow().bold(),
+                        self.output_directory.display(),
+                        err
+                    );
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn render_group(title: &str, results: &[BenchResult], trends: &[BenchTrend]) -> String {
+    let mut out = format!(
+        "<p><a href=\"index.html\">&larr; All groups</a></p>\n<h1>{}</h1>\n",
+        escape(title)
+    );
+    out.push_str(&results_table(results));
+
+    out.push_str("<h2>Median time</h2>\n");
+    let rows: Vec<(String, f64, String)> = results
+        .iter()
+        .map(|result| {
+            (
+                result.bench_id.bench_name.clone(),
+                result.stats.median_ns as f64,
+                format_duration(result.stats.median_ns),
+            )
+        })
+        .collect();
+    out.push_str(&bar_chart(&rows));
+
+    let throughput_rows: Vec<(String, f64, String)> = results
+        .iter()
+        .filter_map(|result| {
+            let input_size_in_bytes = result.input_size_in_bytes?;
+            let median_ns = result.stats.median_ns.max(1);
+            Some((
+                result.bench_id.bench_name.clone(),
+                input_size_in_bytes as f64 / median_ns as f64,
+                format_duration_or_throughput(median_ns, Some(input_size_in_bytes))
+                    .trim()
+                    .to_string(),
+            ))
+        })
+        .collect();
+    if !throughput_rows.is_empty() {
+        out.push_str("<h2>Throughput</h2>\n");
+        out.push_str(&bar_chart(&throughput_rows));
+    }
+
+    let samples: Vec<(String, Vec<f64>)> = results
+        .iter()
+        .filter(|result| !result.raw_samples.durations_ns.is_empty())
+        .map(|result| {
+            let mut durations: Vec<f64> = result
+                .raw_samples
+                .durations_ns
+                .iter()
+                .map(|duration| *duration as f64)
+                .collect();
+            durations.sort_by(|a, b| a.total_cmp(b));
+            (result.bench_id.bench_name.clone(), durations)
+        })
+        .collect();
+    if !samples.is_empty() {
+        out.push_str("<h2>Samples</h2>\n");
+        out.push_str(&box_plot(&samples));
+    }
+
+    let trends: Vec<&BenchTrend> = trends
+        .iter()
+        .filter(|trend| trend.entries.len() >= 2)
+        .collect();
+    if !trends.is_empty() {
+        out.push_str(&format!(
+            "<h2>History</h2>\n<p>The median of the last {} runs.</p>\n",
+            NUM_HISTORY_RUNS
+        ));
+        for trend in trends {
+            out.push_str(&format!(
+                "<h3>{}</h3>\n",
+                escape(&trend.bench_id.bench_name)
+            ));
+            out.push_str(&line_chart(&trend.entries));
+        }
+    }
+    out
+}
+
+fn results_table(results: &[BenchResult]) -> String {
+    let tracked_memory = results.iter().any(|result| result.tracked_memory);
+    let output_value_column_title = results
+        .iter()
+        .find(|result| result.output_value.is_some())
+        .map(|result| result.output_value_column_title);
+    let mut out = String::from("<table>\n<tr><th>Bench</th>");
+    if tracked_memory {
+        out.push_str("<th>Memory</th>");
+    }
+    out.push_str("<th>Avg</th><th>Median</th><th>Min .. Max</th>");
+    if let Some(title) = output_value_column_title {
+        out.push_str(&format!("<th>{}</th>", escape(title)));
+    }
+    out.push_str("</tr>\n");
+    for result in results {
+        let stats = &result.stats;
+        let old_stats = result.old_stats.as_ref();
+        let input_size_in_bytes = result.input_size_in_bytes;
+        let duration = |duration_ns| {
+            format_duration_or_throughput(duration_ns, input_size_in_bytes)
+                .trim()
+                .to_string()
+        };
+        out.push_str(&format!(
+            "<tr><td>{}</td>",
+            escape(&result.bench_id.bench_name)
+        ));
+        if tracked_memory {
+            let memory = if result.tracked_memory {
+                bytes_to_string(stats.avg_memory as u64)
+            } else {
+                String::new()
+            };
+            let old = old_stats.map(|old_stats| old_stats.avg_memory as f64);
+            out.push_str(&format!(
+                "<td>{}{}</td>",
+                memory,
+                delta(stats.avg_memory as f64, old)
+            ));
+        }
+        // The delta is the change of the time, also for throughput
+        out.push_str(&format!(
+            "<td>{}{}</td><td>{}{}</td>",
+            duration(stats.average_ns),
+            delta(
+                stats.average_ns as f64,
+                old_stats.map(|old_stats| old_stats.average_ns as f64)
+            ),
+            duration(stats.median_ns),
+            delta(
+                stats.median_ns as f64,
+                old_stats.map(|old_stats| old_stats.median_ns as f64)
+            ),
+        ));
+        out.push_str(&format!(
+            "<td>{} .. {}</td>",
+            duration(stats.min_ns),
+            duration(stats.max_ns)
+        ));
+        if output_value_column_title.is_some() {
+            out.push_str(&format!(
+                "<td>{} {}</td>",
+                escape(result.output_value.as_deref().unwrap_or_default()),
+                escape(result.output_value_delta.as_deref().unwrap_or_default())
+            ));
+        }
+        out.push_str("</tr>\n");
+    }
+    out.push_str("</table>\n");
+    out
+}
+
+/// The change from `old` to `new` in percent. Increases are marked as regressions.
+fn delta(new: f64, old: Option<f64>) -> String {
+    let Some(old) = old.filter(|old| *old != 0.0 && new != 0.0 && *old != new) else {
+        return String::new();
+    };
+    let diff = compute_percentage_diff(new, old);
+    let class = if diff > 2.0 {
+        "regression"
+    } else if diff < -2.0 {
+        "improvement"
+    } else {
+        "neutral"
+    };
+    format!(" <span class=\"{}\">({:+.2}%)</span>", class, diff)
+}
+
+/// A horizontal bar chart of the rows `(label, value, value label)`.
+fn bar_chart(rows: &[(String, f64, String)]) -> String {
+    let max = rows.iter().map(|(_, value, _)| *value).fold(0.0, f64::max);
+    let bar_width = CHART_WIDTH - LABEL_WIDTH - VALUE_LABEL_WIDTH;
+    let height = rows.len() as f64 * ROW_HEIGHT;
+    let mut out = svg_start(height);
+    for (i, (label, value, value_label)) in rows.iter().enumerate() {
+        let y = i as f64 * ROW_HEIGHT;
+        let width = if max > 0.0 {
+            value / max * bar_width
+        } else {
+            0.0
+        };
+        out.push_str(&row_label(label, y));
+        out.push_str(&format!(
+            "<rect class=\"bar\" x=\"{}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{}\"/>\n",
+            LABEL_WIDTH,
+            y + 4.0,
+            width,
+            ROW_HEIGHT - 8.0
+        ));
+        out.push_str(&format!(
+            "<text x=\"{:.1}\" y=\"{:.1}\">{}</text>\n",
+            LABEL_WIDTH + width + 6.0,
+            y + ROW_HEIGHT / 2.0 + 4.0,
+            escape(value_label)
+        ));
+    }
+    out.push_str("</svg>\n");
+    out
+}
+
+/// Horizontal box plots of the sorted samples of each bench: a whisker from min to max, a box
+/// from the first to the third quartile and a line at the median. All use the same scale.
+fn box_plot(rows: &[(String, Vec<f64>)]) -> String {
+    let min = rows
+        .iter()
+        .map(|(_, samples)| samples[0])
+        .fold(f64::INFINITY, f64::min);
+    let max = rows
+        .iter()
+        .map(|(_, samples)| samples[samples.len() - 1])
+        .fold(0.0, f64::max);
+    let plot_width = CHART_WIDTH - LABEL_WIDTH - VALUE_LABEL_WIDTH;
+    let x = |value: f64| {
+        let range = max - min;
+        let offset = if range > 0.0 {
+            (value - min) / range * plot_width
+        } else {
+            plot_width / 2.0
+        };
+        LABEL_WIDTH + offset
+    };
+    let height = rows.len() as f64 * ROW_HEIGHT + ROW_HEIGHT;
+    let mut out = svg_start(height);
+    for (i, (label, samples)) in rows.iter().enumerate() {
+        let y = i as f64 * ROW_HEIGHT;
+        let center = y + ROW_HEIGHT / 2.0;
+        let [low, q1, median, q3, high] =
+            [0.0, 0.25, 0.5, 0.75, 1.0].map(|p| percentile(samples, p));
+        out.push_str(&row_label(label, y));
+        out.push_str(&format!(
+            "<line class=\"whisker\" x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\"/>\n",
+            x(low),
+            center,
+            x(high),
+            center
+        ));
+        out.push_str(&format!(
+            "<rect class=\"box\" x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{}\">\
+             <title>min {}, q1 {}, median {}, q3 {}, max {}</title></rect>\n",
+            x(q1),
+            y + 4.0,
+            x(q3) - x(q1),
+            ROW_HEIGHT - 8.0,
+            format_duration(low as u64),
+            format_duration(q1 as u64),
+            format_duration(median as u64),
+            format_duration(q3 as u64),
+            format_duration(high as u64),
+        ));
+        out.push_str(&format!(
+            "<line class=\"median\" x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\"/>\n",
+            x(median),
+            y + 4.0,
+            x(median),
+            y + ROW_HEIGHT - 4.0
+        ));
+        out.push_str(&format!(
+            "<text x=\"{:.1}\" y=\"{:.1}\">{}</text>\n",
+            x(high) + 6.0,
+            center + 4.0,
+            format_duration(median as u64)
+        ));
+    }
+    let axis_y = rows.len() as f64 * ROW_HEIGHT + ROW_HEIGHT / 2.0 + 4.0;
+    out.push_str(&format!(
+        "<text class=\"axis\" x=\"{}\" y=\"{:.1}\">{}</text>\n\
+         <text class=\"axis\" x=\"{}\" y=\"{:.1}\" text-anchor=\"end\">{}</text>\n",
+        LABEL_WIDTH,
+        axis_y,
+        format_duration(min as u64),
+        CHART_WIDTH - VALUE_LABEL_WIDTH,
+        axis_y,
+        format_duration(max as u64),
+    ));
+    out.push_str("</svg>\n");
+    out
+}
+
+/// A line chart of the median of the runs, oldest first.
+fn line_chart(entries: &[HistoryEntry]) -> String {
+    const HEIGHT: f64 = 160.0;
+    const PADDING: f64 = 16.0;
+    let max = entries
+        .iter()
+        .map(|entry| entry.stats.median_ns as f64)
+        .fold(0.0, f64::max)
+        .max(1.0);
+    let plot_width = CHART_WIDTH - LABEL_WIDTH - VALUE_LABEL_WIDTH;
+    let point = |i: usize, entry: &HistoryEntry| {
+        let x = LABEL_WIDTH + i as f64 / (entries.len() - 1) as f64 * plot_width;
+        let y = PADDING + (1.0 - entry.stats.median_ns as f64 / max) * (HEIGHT - 2.0 * PADDING);
+        (x, y)
+    };
+    let mut out = svg_start(HEIGHT);
+    out.push_str(&format!(
+        "<text class=\"axis\" x=\"{}\" y=\"{}\" text-anchor=\"end\">{}</text>\n\
+         <text class=\"axis\" x=\"{}\" y=\"{}\" text-anchor=\"end\">0ns</text>\n",
+        LABEL_WIDTH - 8.0,
+        PADDING + 4.0,
+        format_duration(max as u64),
+        LABEL_WIDTH - 8.0,
+        HEIGHT - PADDING + 4.0,
+    ));
+    let points: Vec<String> = entries
+        .iter()
+        .enumerate()
+        .map(|(i, entry)| {
+            let (x, y) = point(i, entry);
+            format!("{:.1},{:.1}", x, y)
+        })
+        .collect();
+    out.push_str(&format!(
+        "<polyline class=\"line\" points=\"{}\"/>\n",
+        points.join(" ")
+    ));
+    for (i, entry) in entries.iter().enumerate() {
+        let (x, y) = point(i, entry);
+        let commit = entry
+            .metadata
+            .as_ref()
+            .and_then(|metadata| metadata.git_commit.as_deref())
+            .map(|commit| format!(" ({})", commit.chars().take(10).collect::<String>()))
+            .unwrap_or_default();
+        out.push_str(&format!(
+            "<circle class=\"point\" cx=\"{:.1}\" cy=\"{:.1}\" r=\"3\">\
+             <title>{}{}</title></circle>\n",
+            x,
+            y,
+            format_duration(entry.stats.median_ns),
+            escape(&commit)
+        ));
+    }
+    out.push_str("</svg>\n");
+    out
+}
+
+fn svg_start(height: f64) -> String {
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" \
+         viewBox=\"0 0 {0} {1}\">\n",
+        CHART_WIDTH, height
+    )
+}
+
+fn row_label(label: &str, y: f64) -> String {
+    format!(
+        "<text x=\"{}\" y=\"{:.1}\" text-anchor=\"end\">{}</text>\n",
+        LABEL_WIDTH - 8.0,
+        y + ROW_HEIGHT / 2.0 + 4.0,
+        escape(label)
+    )
+}
+
+fn page(title: &str, content: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>{}</title>\n<style>{}</style>\n</head>\n<body>\n{}</body>\n</html>\n",
+        escape(title),
+        STYLE,
+        content
+    )
+}
+
+const STYLE: &str = "
+body { font-family: sans-serif; margin: 2em; color: #222; }
+table { border-collapse: collapse; margin-bottom: 1em; }
+th, td { padding: 4px 10px; border-bottom: 1px solid #ddd; text-align: right; }
+th:first-child, td:first-child { text-align: left; }
+svg { display: block; margin-bottom: 1em; font-size: 12px; }
+.bar { fill: #4c78a8; }
+.box { fill: #9ecae9; stroke: #4c78a8; }
+.whisker, .median { stroke: #4c78a8; stroke-width: 2; }
+.line { fill: none; stroke: #4c78a8; stroke-width: 2; }
+.point { fill: #4c78a8; }
+.axis { fill: #666; }
+.regression { color: #c0392b; }
+.improvement { color: #27ae60; }
+.warning { color: #b7791f; }
+";
+
+/// Escapes text for HTML content and attributes.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+/// A file name safe version of the title.
+fn slug(title: &str) -> String {
+    let mut slug = String::new();
+    for c in title.chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.truncate(60);
+    slug.trim_end_matches('-').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::emit_groups_of_two_runners;
+
+    #[test]
+    fn escape_and_slug_test() {
+        assert_eq!(escape("<a & \"b\">"), "&lt;a &amp; &quot;b&quot;&gt;");
+        assert_eq!(slug("runner / Group 1"), "runner-group-1");
+    }
+
+    #[test]
+    fn delta_test() {
+        assert_eq!(delta(100.0, None), "");
+        assert_eq!(delta(100.0, Some(100.0)), "");
+        assert_eq!(
+            delta(110.0, Some(100.0)),
+            " <span class=\"regression\">(+10.00%)</span>"
+        );
+        assert_eq!(
+            delta(99.0, Some(100.0)),
+            " <span class=\"neutral\">(-1.00%)</span>"
+        );
+    }
+
+    #[test]
+    fn charts_test() {
+        let chart = bar_chart(&[
+            ("a".to_string(), 50.0, "50ns".to_string()),
+            ("b".to_string(), 100.0, "100ns".to_string()),
+        ]);
+        // The largest value spans the whole bar area
+        assert!(chart.contains("width=\"235.0\""), "{}", chart);
+        assert!(chart.contains("width=\"470.0\""), "{}", chart);
+
+        let chart = box_plot(&[("a".to_string(), vec![1.0, 2.0, 3.0, 4.0, 5.0])]);
+        assert!(chart.contains("<title>min 1ns, q1 2ns, median 3ns, q3 4ns, max 5ns</title>"));
+    }
+
+    #[test]
+    fn write_report() {
+        let dir = std::env::temp_dir().join("binggan_html_reporter");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("01-stale.html"), "").unwrap();
+        emit_groups_of_two_runners(|| HtmlReporter::with_directory(&dir));
+        HtmlReporter::with_directory(&dir)
+            .add_group(None, Some("<group>"), &[BenchResult::for_test("fib", 200)])
+            .unwrap();
+
+        assert!(!dir.join("01-stale.html").exists());
+        let index = std::fs::read_to_string(dir.join("index.html")).unwrap();
+        assert!(index.contains("<a href=\"01-first-group.html\">first / group</a>"));
+        assert!(index.contains("<a href=\"02-second-group.html\">second / group</a>"));
+        assert!(index.contains("<a href=\"03-group.html\">&lt;group&gt;</a>"));
+        let page = std::fs::read_to_string(dir.join("01-first-group.html")).unwrap();
+        assert!(dir.join("03-group.html").exists());
+        assert!(page.contains("<h2>Median time</h2>"));
+        assert!(page.contains("<h2>Samples</h2>"));
+        assert!(!page.contains("<h2>Throughput</h2>"));
+    }
+}
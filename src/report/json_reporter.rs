@@ -0,0 +1,295 @@
+use std::any::Any;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use miniserde::Serialize;
+use yansi::Paint;
+
+use super::{REPORTER_PLUGIN_NAME, SharedOutputs, print_info, reserve_stdout, with_shared_output};
+use crate::{
+    RunMetadata,
+    bench::BenchResult,
+    bench_runner::num_live_runners,
+    plugins::{EventListener, PerfCounterValues, PluginEvents},
+    stats::BenchStats,
+};
+
+/// The version of the document written by the [JsonReporter].
+const JSON_FORMAT_VERSION: u32 = 1;
+
+/// The plugin name of a [JsonReporter] which writes to a file. It is added next to the default
+/// reporter.
+pub const JSON_REPORTER_PLUGIN_NAME: &str = "json_reporter";
+
+/// The JsonReporter writes the results of the whole run as a single JSON document, e.g. to feed
+/// dashboards without parsing the terminal output.
+///
+/// The document contains the results of all [BenchRunner](crate::BenchRunner)s of the process.
+/// It is written to a file whenever a runner is dropped, or printed to stdout when the last live
+/// runner is dropped. Writing to stdout replaces the default reporter, writing to a file keeps
+/// it. The reporter can also be enabled via `--output-format json` and `--output-path <path>`.
+///
+/// The document has the following structure:
+/// ```json
+/// {
+///   "version": 1,
+///   "metadata": { "git_commit": "...", "rustc_version": "...", ... },
+///   "noise_findings": ["Turbo boost is enabled, ..."],
+///   "runners": [{
+///     "name": "my_runner",
+///     "groups": [{
+///       "name": "my_group",
+///       "benches": [{
+///         "bench_id": { "runner_name": "my_runner", "group_name": "my_group", "bench_name": "my_bench" },
+///         "stats": { "min_ns": 10, "max_ns": 14, "average_ns": 11, "median_ns": 11, "avg_memory": 0 },
+///         "old_stats": null,
+///         "perf_counter": { "Br": 6.0, "BrM": 0.0 },
+///         "old_perf_counter": null,
+///         "memory_bytes": null,
+///         "input_size_in_bytes": 1000,
+///         "throughput_bytes_per_sec": 90909090909.09091,
+///         "output_value": "42",
+///         "output_value_column_title": "Output"
+///       }]
+///     }]
+///   }]
+/// }
+/// ```
+///
+/// ## Usage Example
+/// ```rust,no_run
+/// use binggan::*;
+/// use binggan::report::JsonReporter;
+///
+/// let mut runner = BenchRunner::new();
+/// runner.add_plugin(JsonReporter::to_file("target/binggan/results.json"));
+/// ```
+#[derive(Clone)]
+pub struct JsonReporter {
+    /// The file to write to, stdout if None.
+    output_path: Option<PathBuf>,
+}
+
+/// The documents of all JsonReporters in the process, see [SharedOutputs].
+static DOCUMENTS: SharedOutputs<JsonDocument> = Mutex::new(Vec::new());
+
+impl JsonReporter {
+    /// Creates a JsonReporter, which prints the document to stdout.
+    ///
+    /// The document is printed in one line when the last live [BenchRunner](crate::BenchRunner)
+    /// is dropped. Runners which are created afterwards print another document. Other output of
+    /// binggan, e.g. warnings, is printed to stderr instead, so stdout only contains the
+    /// documents.
+    pub fn to_stdout() -> Self {
+        reserve_stdout();
+        JsonReporter { output_path: None }
+    }
+
+    /// Creates a JsonReporter, which writes the document to the file at `path`.
+    ///
+    /// The file is rewritten whenever a [BenchRunner](crate::BenchRunner) is dropped and contains
+    /// the results of all runners of the process, which write to the same path.
+    pub fn to_file<P: Into<PathBuf>>(path: P) -> Self {
+        JsonReporter {
+            output_path: Some(path.into()),
+        }
+    }
+
+    fn with_document<R>(&self, f: impl FnOnce(&mut JsonDocument) -> R) -> R {
+        with_shared_output(&DOCUMENTS, self.output_path.as_deref(), f)
+    }
+
+    fn add_group(
+        &mut self,
+        runner_name: Option<&str>,
+        group_name: Option<&str>,
+        results: &[BenchResult],
+    ) {
+        self.with_document(|document| {
+            if document.metadata.is_none() {
+                document.metadata = results.iter().find_map(|result| result.metadata.clone());
+            }
+            let runner_name = runner_name.map(str::to_string);
+            let runners = &mut document.runners;
+            if runners
+                .last()
+                .is_none_or(|runner| runner.name != runner_name)
+            {
+                runners.push(JsonRunner {
+                    name: runner_name,
+                    groups: Vec::new(),
+                });
+            }
+            runners.last_mut().unwrap().groups.push(JsonGroup {
+                name: group_name.map(str::to_string),
+                benches: results.iter().map(JsonBench::from_result).collect(),
+            });
+        });
+    }
+
+    fn write(&self) {
+        let Some(output_path) = &self.output_path else {
+            // Runners which are still alive add more groups to the document
+            if num_live_runners() == 0 {
+                let content = self.with_document(|document| {
+                    miniserde::json::to_string(&std::mem::take(document))
+                });
+                println!("{}", content);
+            }
+            return;
+        };
+        let content = self.with_document(|document| miniserde::json::to_string(document));
+        let res = output_path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| std::fs::write(output_path, content));
+        if let Err(err) = res {
+            print_info!(
+                "{} Could not write the results to {}: {}",
+                "Warning:".yellow().bold(),
+                output_path.display(),
+                err
+            );
+        }
+    }
+}
+
+impl EventListener for JsonReporter {
+    fn as_any(&mut self) -> &mut dyn Any {
+        self
+    }
+    fn name(&self) -> &'static str {
+        if self.output_path.is_some() {
+            JSON_REPORTER_PLUGIN_NAME
+        } else {
+            REPORTER_PLUGIN_NAME
+        }
+    }
+    fn on_event(&mut self, event: PluginEvents) {
+        match event {
+            PluginEvents::NoiseFindings { findings } => {
+                self.with_document(|document| {
+                    document.noise_findings =
+                        findings.iter().map(|finding| finding.to_string()).collect();
+                });
+            }
+            PluginEvents::GroupStop {
+                runner_name,
+                group_name,
+                results,
+                output_value_column_title: _,
+            } => {
+                self.add_group(runner_name, group_name, results);
+            }
+            PluginEvents::RunStop => {
+                self.write();
+            }
+            _ => {}
+        }
+    }
+}
+
+#[derive(Serialize, Clone)]
+struct JsonDocument {
+    version: u32,
+    metadata: Option<RunMetadata>,
+    noise_findings: Vec<String>,
+    runners: Vec<JsonRunner>,
+}
+
+impl Default for JsonDocument {
+    fn default() -> Self {
+        JsonDocument {
+            version: JSON_FORMAT_VERSION,
+            metadata: None,
+            noise_findings: Vec::new(),
+            runners: Vec::new(),
+        }
+    }
+}
+
+#[derive(Serialize, Clone)]
+struct JsonRunner {
+    name: Option<String>,
+    groups: Vec<JsonGroup>,
+}
+
+#[derive(Serialize, Clone)]
+struct JsonGroup {
+    name: Option<String>,
+    benches: Vec<JsonBench>,
+}
+
+#[derive(Serialize, Clone)]
+struct JsonBenchId {
+    runner_name: Option<String>,
+    group_name: Option<String>,
+    bench_name: String,
+}
+
+#[derive(Serialize, Clone)]
+struct JsonBench {
+    bench_id: JsonBenchId,
+    stats: BenchStats,
+    old_stats: Option<BenchStats>,
+    perf_counter: Option<BTreeMap<String, f64>>,
+    old_perf_counter: Option<BTreeMap<String, f64>>,
+    memory_bytes: Option<usize>,
+    input_size_in_bytes: Option<usize>,
+    throughput_bytes_per_sec: Option<f64>,
+    output_value: Option<String>,
+    output_value_column_title: String,
+}
+
+impl JsonBench {
+    fn from_result(result: &BenchResult) -> Self {
+        let throughput_bytes_per_sec = result
+            .input_size_in_bytes
+            .filter(|_| result.stats.median_ns > 0)
+            .map(|bytes| bytes as f64 * 1_000_000_000.0 / result.stats.median_ns as f64);
+        JsonBench {
+            bench_id: JsonBenchId {
+                runner_name: result.bench_id.runner_name.clone(),
+                group_name: result.bench_id.group_name.clone(),
+                bench_name: result.bench_id.bench_name.clone(),
+            },
+            stats: result.stats,
+            old_stats: result.old_stats,
+            perf_counter: result.perf_counter.as_ref().map(perf_counter_map),
+            old_perf_counter: result.old_perf_counter.as_ref().map(perf_counter_map),
+            memory_bytes: result.tracked_memory.then_some(result.stats.avg_memory),
+            input_size_in_bytes: result.input_size_in_bytes,
+            throughput_bytes_per_sec,
+            output_value: result.output_value.clone(),
+            output_value_column_title: result.output_value_column_title.to_string(),
+        }
+    }
+}
+
+fn perf_counter_map(perf_counter: &PerfCounterValues) -> BTreeMap<String, f64> {
+    perf_counter
+        .values()
+        .iter()
+        .map(|(counter, value)| (counter.to_string(), *value))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::emit_groups_of_two_runners;
+
+    #[test]
+    fn document_covers_all_runners() {
+        let output_path = std::env::temp_dir()
+            .join("binggan_json_reporter")
+            .join("results.json");
+        let _ = std::fs::remove_file(&output_path);
+        emit_groups_of_two_runners(|| JsonReporter::to_file(&output_path));
+        let content = std::fs::read_to_string(&output_path).unwrap();
+        assert!(content.contains(r#""name":"first""#), "{}", content);
+        assert!(content.contains(r#""name":"second""#), "{}", content);
+    }
+}
@@ -0,0 +1,450 @@
+use std::any::Any;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use yansi::Paint;
+
+use super::{
+    REPORTER_PLUGIN_NAME, SharedOutputs,
+    format::{bytes_to_string, format_duration_or_throughput, format_with_underscores_f64},
+    metadata_diff_str, print_info, reserve_stdout, with_shared_output,
+};
+use crate::{
+    bench::BenchResult,
+    plugins::{EventListener, PerfCounter, PerfCounterValues, PluginEvents},
+    stats::{BenchStats, compute_percentage_diff},
+};
+
+/// The plugin name of a [MarkdownReporter] which writes to a file. It is added next to the
+/// default reporter.
+pub const MARKDOWN_REPORTER_PLUGIN_NAME: &str = "markdown_reporter";
+
+/// Changes above this percentage are marked, the same threshold as for the colors of the
+/// [PlainReporter](crate::report::PlainReporter).
+const SIGNIFICANT_CHANGE_PCT: f64 = 2.0;
+
+type StatsAccessor = fn(&BenchStats) -> u64;
+
+/// How the [MarkdownReporter] marks significant changes compared to the previous run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DeltaMarker {
+    /// 🔴 for regressions and 🟢 for improvements, e.g. `🔴 (+5.00%)`.
+    #[default]
+    Emoji,
+    /// Bold deltas, e.g. `**(+5.00%)**`.
+    Bold,
+}
+
+/// The MarkdownReporter renders each group as a GitHub-flavored markdown table, e.g. to post the
+/// results as a pull request comment in CI.
+///
+/// The table has the columns bench name, memory (if tracked), avg, median, min .. max, the
+/// output value (if any) and one column per perf counter (if enabled). Changes compared to the
+/// previous run are shown with [DeltaMarker]s instead of colors.
+///
+/// Writing to a file rewrites the file after each group, so it contains all groups of the
+/// process, also of other [BenchRunner](crate::BenchRunner)s writing to the same path, and keeps
+/// the default reporter. Writing to stdout replaces the default reporter. The reporter
+/// can also be enabled via `--output-format markdown` and `--output-path <path>`.
+///
+/// e.g.
+/// ```text
+/// ### fibonacci
+///
+/// | Bench | Avg | Median | Min .. Max |
+/// |:--|--:|--:|--:|
+/// | fib 10 | 102ns 🟢 (-4.67%) | 101ns | 98ns .. 130ns |
+/// ```
+///
+/// ## Usage Example
+/// ```rust
+/// use binggan::*;
+/// use binggan::report::MarkdownReporter;
+///
+/// let mut runner = BenchRunner::new();
+/// runner.add_plugin(MarkdownReporter::to_file("target/binggan/results.md"));
+/// ```
+#[derive(Clone)]
+pub struct MarkdownReporter {
+    /// The file to write to, stdout if None.
+    output_path: Option<PathBuf>,
+    delta_marker: DeltaMarker,
+}
+
+/// The content of a markdown file.
+#[derive(Default)]
+struct MarkdownDocument {
+    noise_findings: Vec<String>,
+    /// The rendered groups of the run so far.
+    groups: Vec<String>,
+}
+
+/// The documents of all MarkdownReporters in the process, see [SharedOutputs].
+static DOCUMENTS: SharedOutputs<MarkdownDocument> = Mutex::new(Vec::new());
+
+impl MarkdownReporter {
+    /// Creates a MarkdownReporter, which prints the tables to stdout.
+    ///
+    /// Other output of binggan, e.g. warnings, is printed to stderr instead, so stdout only
+    /// contains the markdown.
+    pub fn to_stdout() -> Self {
+        reserve_stdout();
+        Self::new(None)
+    }
+
+    /// Creates a MarkdownReporter, which writes the tables to the file at `path`.
+    pub fn to_file<P: Into<PathBuf>>(path: P) -> Self {
+        Self::new(Some(path.into()))
+    }
+
+    fn new(output_path: Option<PathBuf>) -> Self {
+        MarkdownReporter {
+            output_path,
+            delta_marker: DeltaMarker::default(),
+        }
+    }
+
+    /// Sets how significant changes are marked, [DeltaMarker::Emoji] by default.
+    pub fn delta_marker(mut self, delta_marker: DeltaMarker) -> Self {
+        self.delta_marker = delta_marker;
+        self
+    }
+
+    fn render_group(
+        &self,
+        runner_name: Option<&str>,
+        group_name: Option<&str>,
+        results: &[BenchResult],
+    ) -> String {
+        let title = [runner_name, group_name]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+            .join(" / ");
+        let mut out = String::new();
+        if !title.is_empty() {
+            out.push_str(&format!("### {}\n\n", escape(&title)));
+        }
+        if let Some(diff) = metadata_diff_str(results) {
+            for line in diff.lines() {
+                out.push_str(&format!("> {}\n", line.trim()));
+            }
+            out.push('\n');
+        }
+
+        let tracked_memory = results.iter().any(|result| result.tracked_memory);
+        let output_value_column_title = results
+            .iter()
+            .find(|result| result.output_value.is_some())
+            .map(|result| result.output_value_column_title);
+        let perf_counters: Vec<PerfCounter> = results
+            .iter()
+            .find_map(|result| result.perf_counter.as_ref())
+            .map(|perf_counter| {
+                perf_counter
+                    .values()
+                    .iter()
+                    .map(|(counter, _)| *counter)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let mut header = vec!["Bench".to_string()];
+        if tracked_memory {
+            header.push("Memory".to_string());
+        }
+        header.extend(["Avg", "Median", "Min .. Max"].map(str::to_string));
+        header.extend(output_value_column_title.map(str::to_string));
+        header.extend(perf_counters.iter().map(|counter| counter.to_string()));
+        push_row(&mut out, &header);
+        let alignment: Vec<String> = (0..header.len())
+            .map(|i| if i == 0 { ":--" } else { "--:" }.to_string())
+            .collect();
+        push_row(&mut out, &alignment);
+
+        for result in results {
+            let stats = &result.stats;
+            let input_size_in_bytes = result.input_size_in_bytes;
+            let mut row = vec![result.bench_id.bench_name.clone()];
+            if tracked_memory {
+                let memory = if result.tracked_memory {
+                    with_delta(
+                        bytes_to_string(stats.avg_memory as u64),
+                        self.stats_delta(result, |stats| stats.avg_memory as u64, true),
+                    )
+                } else {
+                    String::new()
+                };
+                row.push(memory);
+            }
+            // Throughput is the inverse of the time, so bigger is better
+            let smaller_is_better = input_size_in_bytes.is_none();
+            let columns: [StatsAccessor; 2] = [|stats| stats.average_ns, |stats| stats.median_ns];
+            for get in columns {
+                row.push(with_delta(
+                    duration_or_throughput(get(stats), input_size_in_bytes),
+                    self.stats_delta(result, get, smaller_is_better),
+                ));
+            }
+            let (min, max) = if input_size_in_bytes.is_some() {
+                // The min time is the max throughput
+                (stats.max_ns, stats.min_ns)
+            } else {
+                (stats.min_ns, stats.max_ns)
+            };
+            row.push(format!(
+                "{} .. {}",
+                duration_or_throughput(min, input_size_in_bytes),
+                duration_or_throughput(max, input_size_in_bytes)
+            ));
+            if output_value_column_title.is_some() {
+                row.push(with_delta(
+                    result.output_value.clone().unwrap_or_default(),
+                    result.output_value_delta.clone().unwrap_or_default(),
+                ));
+            }
+            for counter in &perf_counters {
+                let value = |perf_counter: Option<&PerfCounterValues>| {
+                    perf_counter?
+                        .values()
+                        .iter()
+                        .find(|(other, _)| other == counter)
+                        .map(|(_, value)| *value)
+                };
+                let column = match value(result.perf_counter.as_ref()) {
+                    Some(new) => {
+                        let delta = value(result.old_perf_counter.as_ref())
+                            .map(|old| self.format_delta(new, old, true))
+                            .unwrap_or_default();
+                        with_delta(format_with_underscores_f64(new), delta)
+                    }
+                    None => String::new(),
+                };
+                row.push(column);
+            }
+            push_row(&mut out, &row);
+        }
+        out
+    }
+
+    fn stats_delta(
+        &self,
+        result: &BenchResult,
+        get: StatsAccessor,
+        smaller_is_better: bool,
+    ) -> String {
+        let Some(old_stats) = result.old_stats.as_ref() else {
+            return String::new();
+        };
+        let (new, old) = (get(&result.stats) as f64, get(old_stats) as f64);
+        if smaller_is_better {
+            self.format_delta(new, old, true)
+        } else {
+            // The change of the throughput
+            self.format_delta(old, new, false)
+        }
+    }
+
+    /// Formats the change from `old` to `new` in percent, with a marker if it is significant.
+    fn format_delta(&self, new: f64, old: f64, smaller_is_better: bool) -> String {
+        if new == 0.0 || old == 0.0 || new == old {
+            return String::new();
+        }
+        let diff = compute_percentage_diff(new, old);
+        let diff_str = if diff >= 0.0 {
+            format!("(+{:.2}%)", diff)
+        } else {
+            format!("({:.2}%)", diff)
+        };
+        if diff.abs() <= SIGNIFICANT_CHANGE_PCT {
+            return diff_str;
+        }
+        match self.delta_marker {
+            DeltaMarker::Emoji => {
+                let is_regression = (diff > 0.0) == smaller_is_better;
+                let emoji = if is_regression { "🔴" } else { "🟢" };
+                format!("{} {}", emoji, diff_str)
+            }
+            DeltaMarker::Bold => format!("**{}**", diff_str),
+        }
+    }
+
+    /// Rewrites the file with the shared document of its path.
+    fn write(&self, output_path: &Path) {
+        let content = with_shared_output(&DOCUMENTS, Some(output_path), |document| {
+            document.to_markdown()
+        });
+        let res = output_path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| std::fs::write(output_path, content));
+        if let Err(err) = res {
+            print_info!(
+                "{} Could not write the results to {}: {}",
+                "Warning:".yellow().bold(),
+                output_path.display(),
+                err
+            );
+        }
+    }
+}
+
+impl MarkdownDocument {
+    fn to_markdown(&self) -> String {
+        let mut out = render_noise_findings(&self.noise_findings);
+        out.push_str(&self.groups.join("\n"));
+        out
+    }
+}
+
+fn render_noise_findings(findings: &[String]) -> String {
+    let mut out = String::new();
+    for finding in findings {
+        out.push_str(&format!("> **Warning:** {}\n", escape(finding)));
+    }
+    if !findings.is_empty() {
+        out.push('\n');
+    }
+    out
+}
+
+impl EventListener for MarkdownReporter {
+    fn as_any(&mut self) -> &mut dyn Any {
+        self
+    }
+    fn name(&self) -> &'static str {
+        if self.output_path.is_some() {
+            MARKDOWN_REPORTER_PLUGIN_NAME
+        } else {
+            REPORTER_PLUGIN_NAME
+        }
+    }
+    fn on_event(&mut self, event: PluginEvents) {
+        match event {
+            PluginEvents::NoiseFindings { findings } => {
+                let findings: Vec<String> =
+                    findings.iter().map(|finding| finding.to_string()).collect();
+                match &self.output_path {
+                    Some(output_path) => {
+                        with_shared_output(&DOCUMENTS, Some(output_path), |document| {
+                            document.noise_findings = findings;
+                        });
+                    }
+                    None => print!("{}", render_noise_findings(&findings)),
+                }
+            }
+            PluginEvents::GroupStop {
+                runner_name,
+                group_name,
+                results,
+                output_value_column_title: _,
+            } => {
+                let group = self.render_group(runner_name, group_name, results);
+                match &self.output_path {
+                    Some(output_path) => {
+                        with_shared_output(&DOCUMENTS, Some(output_path), |document| {
+                            document.groups.push(group);
+                        });
+                        self.write(output_path);
+                    }
+                    None => println!("{}", group),
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn duration_or_throughput(duration_ns: u64, input_size_in_bytes: Option<usize>) -> String {
+    format_duration_or_throughput(duration_ns, input_size_in_bytes)
+        .trim()
+        .to_string()
+}
+
+fn with_delta(value: String, delta: String) -> String {
+    if delta.is_empty() {
+        value
+    } else {
+        format!("{} {}", value, delta)
+    }
+}
+
+fn push_row(out: &mut String, columns: &[String]) {
+    let columns: Vec<String> = columns.iter().map(|column| escape(column)).collect();
+    out.push_str(&format!("| {} |\n", columns.join(" | ")));
+}
+
+/// Escapes characters which would break the table or the markdown formatting.
+pub(super) fn escape(text: &str) -> String {
+    text.replace('|', "\\|").replace(['\n', '\r'], " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::emit_groups_of_two_runners;
+
+    fn result(bench_name: &str, old_median_ns: u64) -> BenchResult {
+        BenchResult {
+            old_stats: Some(BenchResult::for_test(bench_name, old_median_ns).stats),
+            ..BenchResult::for_test(bench_name, 100)
+        }
+    }
+
+    #[test]
+    fn render_group_test() {
+        let reporter = MarkdownReporter::to_stdout();
+        let mut with_memory = BenchResult {
+            tracked_memory: true,
+            output_value: Some("42".to_string()),
+            output_value_delta: Some("(+5.00%)".to_string()),
+            ..result("c", 101)
+        };
+        with_memory.stats.avg_memory = 1000;
+        let results = [result("a|b", 110), with_memory];
+        assert_eq!(
+            reporter.render_group(Some("runner"), Some("group"), &results),
+            "### runner / group\n\n\
+             | Bench | Memory | Avg | Median | Min .. Max | Output |\n\
+             | :-- | --: | --: | --: | --: | --: |\n\
+             | a\\|b |  | 100ns 🟢 (-9.09%) | 100ns 🟢 (-9.09%) | 50ns .. 200ns |  |\n\
+             | c | 1.0 KB | 100ns (-0.99%) | 100ns (-0.99%) | 50ns .. 200ns | 42 (+5.00%) |\n"
+        );
+    }
+
+    #[test]
+    fn throughput_test() {
+        let reporter = MarkdownReporter::to_stdout();
+        let results = [BenchResult {
+            input_size_in_bytes: Some(1000),
+            ..result("a", 200)
+        }];
+        let out = reporter.render_group(None, None, &results);
+        // Half the time is twice the throughput
+        assert!(out.contains("| a | 10.000 GB/s 🟢 (+100.00%) |"), "{}", out);
+        assert!(out.contains("| 5.0000 GB/s .. 20.000 GB/s |"), "{}", out);
+    }
+
+    #[test]
+    fn delta_marker_test() {
+        let reporter = MarkdownReporter::to_stdout();
+        assert_eq!(reporter.format_delta(105.0, 100.0, true), "🔴 (+5.00%)");
+        assert_eq!(reporter.format_delta(105.0, 100.0, false), "🟢 (+5.00%)");
+        assert_eq!(reporter.format_delta(101.0, 100.0, true), "(+1.00%)");
+        assert_eq!(reporter.format_delta(100.0, 100.0, true), "");
+        let reporter = reporter.delta_marker(DeltaMarker::Bold);
+        assert_eq!(reporter.format_delta(95.0, 100.0, true), "**(-5.00%)**");
+    }
+
+    #[test]
+    fn write_markdown_file() {
+        let dir = std::env::temp_dir().join("binggan_markdown_reporter");
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = dir.join("results.md");
+        emit_groups_of_two_runners(|| MarkdownReporter::to_file(&path));
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(content.starts_with("### first / group\n"), "{}", content);
+        assert!(content.contains("\n### second / group\n"), "{}", content);
+    }
+}
@@ -0,0 +1,295 @@
+use std::any::Any;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use miniserde::Serialize;
+use yansi::Paint;
+
+use super::{SharedOutputs, print_info, with_shared_output};
+use crate::{
+    bench::BenchResult,
+    bench_id::BenchId,
+    plugins::{EventListener, PluginEvents},
+};
+
+/// The plugin name of a [MetricsExporter] in the github-action-benchmark format.
+pub const GITHUB_ACTION_BENCHMARK_EXPORTER_PLUGIN_NAME: &str = "github_action_benchmark_exporter";
+/// The plugin name of a [MetricsExporter] in the Bencher Metric Format.
+pub const BMF_EXPORTER_PLUGIN_NAME: &str = "bmf_exporter";
+
+/// The format of the [MetricsExporter].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetricsFormat {
+    /// The `customSmallerIsBetter` format of
+    /// [github-action-benchmark](https://github.com/benchmark-action/github-action-benchmark),
+    /// an array of `{"name", "unit", "value"}`. The name is `<bench> - <metric>`.
+    GithubActionBenchmark,
+    /// The [Bencher Metric Format](https://bencher.dev/docs/reference/bencher-metric-format/),
+    /// an object of benches, each an object of metrics with a `value`.
+    Bmf,
+}
+
+/// The MetricsExporter writes the results as metrics for tools which track benchmarks over time
+/// in CI, see [MetricsFormat] for the formats.
+///
+/// Every [BenchStats](crate::report::BenchStats) measure in nanoseconds, the peak memory if it is
+/// tracked and every perf counter per iteration become a separately named metric:
+/// `min`, `max`, `average`, `median`, `peak_memory` and the short name of the counter, e.g.
+/// `Cycles`. The file is rewritten after each group and contains all groups of the process,
+/// also of other [BenchRunner](crate::BenchRunner)s writing to the same path.
+///
+/// The bench names are [BenchId::get_full_name] by default. A template can be set with
+/// [MetricsExporter::name_template].
+///
+/// ## Usage Example
+/// ```rust
+/// use binggan::*;
+/// use binggan::report::{MetricsExporter, MetricsFormat};
+///
+/// let mut runner = BenchRunner::new();
+/// runner.add_plugin(
+///     MetricsExporter::new(MetricsFormat::GithubActionBenchmark, "target/binggan/metrics.json")
+///         .name_template("{group}/{bench}"),
+/// );
+/// ```
+#[derive(Clone)]
+pub struct MetricsExporter {
+    format: MetricsFormat,
+    output_path: PathBuf,
+    name_template: Option<String>,
+}
+
+/// The metrics of the run so far by bench name, of all MetricsExporters in the process, see
+/// [SharedOutputs].
+static METRICS: SharedOutputs<Vec<(String, Vec<Metric>)>> = Mutex::new(Vec::new());
+
+#[derive(Debug, Clone, PartialEq)]
+struct Metric {
+    name: String,
+    unit: &'static str,
+    value: f64,
+}
+
+impl MetricsExporter {
+    /// Creates a MetricsExporter, which writes the format to the file at `path`.
+    pub fn new<P: Into<PathBuf>>(format: MetricsFormat, path: P) -> Self {
+        MetricsExporter {
+            format,
+            output_path: path.into(),
+            name_template: None,
+        }
+    }
+
+    /// Sets the template for the bench names. `{runner}`, `{group}` and `{bench}` are replaced
+    /// by the parts of the [BenchId], missing parts by an empty string.
+    pub fn name_template<S: Into<String>>(mut self, template: S) -> Self {
+        self.name_template = Some(template.into());
+        self
+    }
+
+    fn bench_name(&self, bench_id: &BenchId) -> String {
+        match &self.name_template {
+            Some(template) => template
+                .replace(
+                    "{runner}",
+                    bench_id.runner_name.as_deref().unwrap_or_default(),
+                )
+                .replace(
+                    "{group}",
+                    bench_id.group_name.as_deref().unwrap_or_default(),
+                )
+                .replace("{bench}", &bench_id.bench_name),
+            None => bench_id.get_full_name(),
+        }
+    }
+
+    fn with_metrics<R>(&self, f: impl FnOnce(&mut Vec<(String, Vec<Metric>)>) -> R) -> R {
+        with_shared_output(&METRICS, Some(&self.output_path), f)
+    }
+
+    fn add_group(&mut self, results: &[BenchResult]) {
+        let new_metrics: Vec<(String, Vec<Metric>)> = results
+            .iter()
+            .map(|result| (self.bench_name(&result.bench_id), metrics(result)))
+            .collect();
+        self.with_metrics(|metrics| metrics.extend(new_metrics));
+    }
+
+    fn to_json(&self) -> String {
+        self.with_metrics(|metrics| match self.format {
+            MetricsFormat::GithubActionBenchmark => {
+                let entries: Vec<GithubActionBenchmarkEntry> = metrics
+                    .iter()
+                    .flat_map(|(bench_name, metrics)| {
+                        metrics
+                            .iter()
+                            .map(move |metric| GithubActionBenchmarkEntry {
+                                name: format!("{} - {}", bench_name, metric.name),
+                                unit: metric.unit.to_string(),
+                                value: metric.value,
+                            })
+                    })
+                    .collect();
+                miniserde::json::to_string(&entries)
+            }
+            MetricsFormat::Bmf => {
+                let benches: BTreeMap<String, BTreeMap<String, BmfMetric>> = metrics
+                    .iter()
+                    .map(|(bench_name, metrics)| {
+                        let metrics = metrics
+                            .iter()
+                            .map(|metric| {
+                                (
+                                    metric.name.clone(),
+                                    BmfMetric {
+                                        value: metric.value,
+                                    },
+                                )
+                            })
+                            .collect();
+                        (bench_name.clone(), metrics)
+                    })
+                    .collect();
+                miniserde::json::to_string(&benches)
+            }
+        })
+    }
+
+    fn write(&self) -> std::io::Result<()> {
+        if let Some(parent) = self.output_path.parent() {
+            if !parent.as_os_str().is_empty() {
+                std::fs::create_dir_all(parent)?;
+            }
+        }
+        std::fs::write(&self.output_path, self.to_json())
+    }
+}
+
+/// Returns the metrics of the result.
+fn metrics(result: &BenchResult) -> Vec<Metric> {
+    let stats = &result.stats;
+    let mut metrics: Vec<Metric> = [
+        ("min", stats.min_ns),
+        ("max", stats.max_ns),
+        ("average", stats.average_ns),
+        ("median", stats.median_ns),
+    ]
+    .into_iter()
+    .map(|(name, value)| Metric {
+        name: name.to_string(),
+        unit: "ns",
+        value: value as f64,
+    })
+    .collect();
+    if result.tracked_memory {
+        metrics.push(Metric {
+            name: "peak_memory".to_string(),
+            unit: "bytes",
+            value: stats.avg_memory as f64,
+        });
+    }
+    if let Some(perf_counter) = &result.perf_counter {
+        metrics.extend(perf_counter.values().iter().map(|(counter, value)| Metric {
+            name: counter.to_string(),
+            unit: "count",
+            value: *value,
+        }));
+    }
+    metrics
+}
+
+#[derive(Serialize)]
+struct GithubActionBenchmarkEntry {
+    name: String,
+    unit: String,
+    value: f64,
+}
+
+#[derive(Serialize)]
+struct BmfMetric {
+    value: f64,
+}
+
+impl EventListener for MetricsExporter {
+    fn as_any(&mut self) -> &mut dyn Any {
+        self
+    }
+    fn name(&self) -> &'static str {
+        match self.format {
+            MetricsFormat::GithubActionBenchmark => GITHUB_ACTION_BENCHMARK_EXPORTER_PLUGIN_NAME,
+            MetricsFormat::Bmf => BMF_EXPORTER_PLUGIN_NAME,
+        }
+    }
+    fn on_event(&mut self, event: PluginEvents) {
+        if let PluginEvents::GroupStop { results, .. } = event {
+            self.add_group(results);
+            if let Err(err) = self.write() {
+                print_info!(
+                    "{} Could not write the metrics to {}: {}",
+                    "Warning:".yellow().bold(),
+                    self.output_path.display(),
+                    err
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::emit_groups_of_two_runners;
+
+    fn result() -> BenchResult {
+        let mut result = BenchResult::for_test("fib", 3);
+        result.bench_id = result.bench_id.runner_name(Some("runner"));
+        result.tracked_memory = true;
+        result.stats.avg_memory = 1024;
+        result
+    }
+
+    #[test]
+    fn name_template_test() {
+        let exporter = MetricsExporter::new(MetricsFormat::Bmf, "metrics.json");
+        assert_eq!(exporter.bench_name(&result().bench_id), "runner_group_fib");
+        let exporter = exporter.name_template("{group}/{bench}{runner}");
+        assert_eq!(exporter.bench_name(&result().bench_id), "group/fibrunner");
+    }
+
+    #[test]
+    fn github_action_benchmark_format() {
+        let mut exporter = MetricsExporter::new(MetricsFormat::GithubActionBenchmark, "gab.json")
+            .name_template("{bench}");
+        exporter.add_group(&[result()]);
+        assert_eq!(
+            exporter.to_json(),
+            r#"[{"name":"fib - min","unit":"ns","value":1.0},{"name":"fib - max","unit":"ns","value":6.0},{"name":"fib - average","unit":"ns","value":3.0},{"name":"fib - median","unit":"ns","value":3.0},{"name":"fib - peak_memory","unit":"bytes","value":1024.0}]"#
+        );
+    }
+
+    #[test]
+    fn bmf_format() {
+        let mut exporter =
+            MetricsExporter::new(MetricsFormat::Bmf, "bmf.json").name_template("{bench}");
+        exporter.add_group(&[result()]);
+        assert_eq!(
+            exporter.to_json(),
+            r#"{"fib":{"average":{"value":3.0},"max":{"value":6.0},"median":{"value":3.0},"min":{"value":1.0},"peak_memory":{"value":1024.0}}}"#
+        );
+    }
+
+    #[test]
+    fn metrics_cover_all_runners() {
+        let output_path = std::env::temp_dir()
+            .join("binggan_metrics_exporter")
+            .join("runners.json");
+        emit_groups_of_two_runners(|| {
+            MetricsExporter::new(MetricsFormat::Bmf, &output_path).name_template("{bench}")
+        });
+        let exporter = MetricsExporter::new(MetricsFormat::Bmf, &output_path);
+        let json = exporter.to_json();
+        assert!(json.contains(r#""first""#), "{}", json);
+        assert!(json.contains(r#""second""#), "{}", json);
+    }
+}
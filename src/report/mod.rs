@@ -18,27 +18,56 @@
 //! runner.get_plugin_manager().replace_plugin(PlainReporter::default().print_num_iter(true));
 //! ```
 
+/// The criterion_writer
+mod criterion_writer;
+/// The csv_reporter
+mod csv_reporter;
 /// Helper methods to format benchmark results
 pub mod format;
+/// The html_reporter
+mod html_reporter;
+/// The json_reporter
+mod json_reporter;
+/// The markdown_reporter
+mod markdown_reporter;
+/// The metrics_exporter
+mod metrics_exporter;
 /// The plain_reporter
 mod plain_reporter;
 /// The table_reporter
 #[cfg(feature = "table_reporter")]
 mod table_reporter;
 
-pub use crate::stats::BenchStats;
+pub use crate::stats::{BenchStats, RawSamples};
+pub use criterion_writer::{CRITERION_WRITER_PLUGIN_NAME, CriterionWriter};
+pub use csv_reporter::{CSV_REPORTER_PLUGIN_NAME, CsvReporter};
+pub use html_reporter::{HTML_REPORTER_PLUGIN_NAME, HtmlReporter};
+pub use json_reporter::{JSON_REPORTER_PLUGIN_NAME, JsonReporter};
+pub use markdown_reporter::{DeltaMarker, MARKDOWN_REPORTER_PLUGIN_NAME, MarkdownReporter};
+pub use metrics_exporter::{
+    BMF_EXPORTER_PLUGIN_NAME, GITHUB_ACTION_BENCHMARK_EXPORTER_PLUGIN_NAME, MetricsExporter,
+    MetricsFormat,
+};
 pub use plain_reporter::PlainReporter;
+pub(crate) use plain_reporter::print_table;
 
 #[cfg_attr(docsrs, doc(cfg(feature = "table_reporter")))]
 #[cfg(feature = "table_reporter")]
-pub use table_reporter::TableReporter;
+pub use table_reporter::{ExtraStat, TableOutput, TableReporter};
+
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, PoisonError};
 
 use yansi::Paint;
 
 use format::{bytes_to_string, format_duration_or_throughput};
 
 use crate::{
-    bench::Bench,
+    Config, NoiseFinding, RunMetadata,
+    bench::{Bench, BenchResult},
+    history::append_to_history,
     plugins::{PluginEvents, PluginManager},
     stats::compute_diff,
     write_results::write_results_to_disk,
@@ -48,21 +77,66 @@ use crate::{
 /// reporter.
 pub const REPORTER_PLUGIN_NAME: &str = "reporter";
 
+/// The output format of the results, see [Config::output_format](crate::Config::output_format).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    /// Human readable output by the [PlainReporter], or any other reporter set.
+    #[default]
+    Plain,
+    /// A single JSON document for the whole run by the [JsonReporter].
+    Json,
+    /// A markdown table per group by the [MarkdownReporter].
+    Markdown,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "plain" => Ok(OutputFormat::Plain),
+            "json" => Ok(OutputFormat::Json),
+            "markdown" => Ok(OutputFormat::Markdown),
+            _ => Err(format!(
+                "Invalid output format: {}. Expected one of: plain, json, markdown",
+                s
+            )),
+        }
+    }
+}
+
 pub(crate) fn report_group<'a>(
     runner_name: Option<&str>,
     group_name: Option<&str>,
     benches: &mut [Box<dyn Bench<'a> + 'a>],
     output_value_column_title: &'static str,
+    noise_findings: &[NoiseFinding],
+    config: &Config,
     events: &mut PluginManager,
-) {
+) -> Vec<BenchResult> {
     if benches.is_empty() {
-        return;
+        return Vec::new();
     }
 
+    let metadata = RunMetadata::collect(config);
     let mut results = Vec::new();
     for bench in benches.iter_mut() {
-        let result = bench.get_results(events);
-        write_results_to_disk(&result);
+        let mut result = bench.get_results(events, config.baseline.as_deref());
+        result.noise_findings = noise_findings.to_vec();
+        result.metadata = Some(metadata.clone());
+        write_results_to_disk(
+            &result,
+            config.save_baseline.as_deref(),
+            config.store_raw_samples,
+        );
+        if let Err(err) = append_to_history(&result) {
+            print_info!(
+                "{} Could not append to the history of {}: {}",
+                "Warning:".yellow().bold(),
+                result.bench_id,
+                err
+            );
+        }
         results.push(result);
     }
     events.emit(PluginEvents::GroupStop {
@@ -71,8 +145,80 @@ pub(crate) fn report_group<'a>(
         results: &results,
         output_value_column_title,
     });
+    results
+}
+
+/// Returns a header listing the differences between the environment of the current run and the
+/// run it is compared with, if there are any.
+pub(crate) fn metadata_diff_str(results: &[BenchResult]) -> Option<String> {
+    let (metadata, old_metadata) = results
+        .iter()
+        .find_map(|result| Some((result.metadata.as_ref()?, result.old_metadata.as_ref()?)))?;
+    let diff = metadata.diff(old_metadata);
+    if diff.is_empty() {
+        return None;
+    }
+    let mut out = "Environment differs from the compared run:".to_string();
+    for (name, old, new) in diff {
+        out.push_str(&format!("\n  {}: {} -> {}", name, old, new));
+    }
+    Some(out)
+}
+
+/// The output of a reporter which is shared by all its instances in the process, keyed by the
+/// output path, `None` for stdout.
+///
+/// Each [BenchRunner](crate::BenchRunner) has its own plugins, so a process with multiple runners,
+/// e.g. one per [InputGroup](crate::InputGroup), has multiple instances of a reporter. They
+/// accumulate into the shared output, so it covers all runners instead of only the last one.
+pub(crate) type SharedOutputs<T> = Mutex<Vec<(Option<PathBuf>, T)>>;
+
+/// Runs `f` on the shared output of `output_path`. It is created with `T::default()` on first
+/// use.
+pub(crate) fn with_shared_output<T: Default, R>(
+    outputs: &SharedOutputs<T>,
+    output_path: Option<&Path>,
+    f: impl FnOnce(&mut T) -> R,
+) -> R {
+    let mut outputs = outputs.lock().unwrap_or_else(PoisonError::into_inner);
+    let index = match outputs
+        .iter()
+        .position(|(path, _)| path.as_deref() == output_path)
+    {
+        Some(index) => index,
+        None => {
+            outputs.push((output_path.map(Path::to_path_buf), T::default()));
+            outputs.len() - 1
+        }
+    };
+    f(&mut outputs[index].1)
+}
+
+static STDOUT_RESERVED: AtomicBool = AtomicBool::new(false);
+
+/// Reserves stdout for a machine-readable document, e.g. of the [JsonReporter]. Other output,
+/// like warnings, is printed to stderr afterwards, so it doesn't corrupt the document.
+pub(crate) fn reserve_stdout() {
+    STDOUT_RESERVED.store(true, Ordering::Relaxed);
+}
+
+/// Whether stdout is reserved, see [reserve_stdout].
+pub(crate) fn stdout_reserved() -> bool {
+    STDOUT_RESERVED.load(Ordering::Relaxed)
 }
 
+/// Like `println!`, but prints to stderr if stdout is reserved for a machine-readable document.
+macro_rules! print_info {
+    ($($arg:tt)*) => {
+        if $crate::report::stdout_reserved() {
+            eprintln!($($arg)*);
+        } else {
+            println!($($arg)*);
+        }
+    };
+}
+pub(crate) use print_info;
+
 pub(crate) fn avg_median_str(
     stats: &BenchStats,
     input_size_in_bytes: Option<usize>,
@@ -194,7 +340,7 @@ impl PrintOnce {
     /// Print the name. This will only print the name once.
     pub fn print_name(&self) {
         self.inner.print_once.call_once(|| {
-            println!("{}", self.get_name().black().on_red().invert().bold());
+            print_info!("{}", self.get_name().black().on_red().invert().bold());
         });
     }
     /// Get the name
@@ -202,3 +348,21 @@ impl PrintOnce {
         &self.inner.name
     }
 }
+
+/// Emits a group and `RunStop` for the runners `first` and `second`, each with its own reporter
+/// like separate [BenchRunner](crate::BenchRunner)s in the same process.
+#[cfg(test)]
+pub(crate) fn emit_groups_of_two_runners<R: crate::plugins::EventListener>(
+    new_reporter: impl Fn() -> R,
+) {
+    for runner_name in ["first", "second"] {
+        let mut reporter = new_reporter();
+        reporter.on_event(PluginEvents::GroupStop {
+            runner_name: Some(runner_name),
+            group_name: Some("group"),
+            results: &[BenchResult::for_test(runner_name, 100)],
+            output_value_column_title: "Output",
+        });
+        reporter.on_event(PluginEvents::RunStop);
+    }
+}
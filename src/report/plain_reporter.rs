@@ -2,7 +2,10 @@ use std::any::Any;
 
 use yansi::Paint;
 
-use super::{REPORTER_PLUGIN_NAME, avg_median_str, memory_str, min_max_str};
+use super::{
+    REPORTER_PLUGIN_NAME, avg_median_str, format::format_duration, memory_str, metadata_diff_str,
+    min_max_str, print_info,
+};
 use crate::{
     bench::BenchResult,
     plugins::{EventListener, PluginEvents},
@@ -21,6 +24,8 @@ use crate::{
 pub struct PlainReporter {
     print_runner_name_once: Option<PrintOnce>,
     print_num_iter: bool,
+    /// The last printed environment difference, to print it only once.
+    last_metadata_diff: Option<String>,
 }
 
 impl EventListener for PlainReporter {
@@ -33,6 +38,21 @@ impl EventListener for PlainReporter {
     fn on_event(&mut self, event: PluginEvents) {
         match event {
             PluginEvents::BenchStart { bench_id: _ } => {}
+            PluginEvents::NoiseFindings { findings } => {
+                for finding in findings {
+                    println!("{} {}", "Warning:".yellow().bold(), finding);
+                }
+            }
+            PluginEvents::InputBuilt {
+                input_name,
+                duration,
+            } => {
+                println!(
+                    "Built input {} in {}",
+                    input_name,
+                    format_duration(duration)
+                );
+            }
             PluginEvents::GroupStart {
                 runner_name,
                 group_name: Some(group_name),
@@ -53,14 +73,20 @@ impl EventListener for PlainReporter {
                 runner_name: _,
                 group_name: _,
                 results,
-                output_value_column_title,
+                output_value_column_title: _,
             } => {
+                if let Some(diff) = metadata_diff_str(results) {
+                    if self.last_metadata_diff.as_ref() != Some(&diff) {
+                        println!("{}", diff.yellow());
+                        self.last_metadata_diff = Some(diff);
+                    }
+                }
                 let mut table_data: Vec<Vec<String>> = Vec::new();
 
                 for result in results {
                     let perf_counter = &result.perf_counter;
 
-                    let mut stats_columns = self.to_columns(result, output_value_column_title);
+                    let mut stats_columns = self.to_columns(result);
                     stats_columns.insert(0, result.bench_id.bench_name.to_string());
                     table_data.push(stats_columns);
 
@@ -82,6 +108,7 @@ impl PlainReporter {
     pub fn new() -> Self {
         Self {
             print_runner_name_once: None,
+            last_metadata_diff: None,
             print_num_iter: false,
         }
     }
@@ -91,11 +118,7 @@ impl PlainReporter {
         self
     }
 
-    pub(crate) fn to_columns(
-        &self,
-        result: &BenchResult,
-        output_value_column_title: &'static str,
-    ) -> Vec<String> {
+    pub(crate) fn to_columns(&self, result: &BenchResult) -> Vec<String> {
         let stats = result.stats;
         let other = result.old_stats;
         let input_size_in_bytes = result.input_size_in_bytes;
@@ -113,7 +136,7 @@ impl PlainReporter {
                 min_max,
                 format!(
                     "{}: {}{}",
-                    output_value_column_title,
+                    result.output_value_column_title,
                     output_value,
                     result
                         .output_value_delta
@@ -127,31 +150,8 @@ impl PlainReporter {
         }
     }
 
-    fn print_table(&self, table_data: &Vec<Vec<String>>) {
-        if table_data.is_empty() {
-            return;
-        }
-
-        // Find the maximum number of columns in any row
-        let num_cols = table_data.iter().map(|row| row.len()).max().unwrap_or(0);
-
-        // Calculate the maximum width of each column
-        let mut column_width = vec![0; num_cols];
-        for row in table_data {
-            for (i, cell) in row.iter().enumerate() {
-                let cell = cell.resetting().to_string();
-                column_width[i] = column_width[i].max(cell.count_characters() + 4);
-            }
-        }
-
-        // Print each row with padded cells for alignment
-        for row in table_data {
-            for (i, cell) in row.iter().enumerate() {
-                let padding = column_width[i] - cell.resetting().to_string().count_characters();
-                print!("{}{}", cell, " ".repeat(padding),);
-            }
-            println!(); // Newline at the end of each row
-        }
+    fn print_table(&self, table_data: &[Vec<String>]) {
+        print_table(table_data);
     }
 }
 
@@ -161,8 +161,42 @@ impl Default for PlainReporter {
     }
 }
 
+/// Prints the rows with aligned columns. Color codes are ignored for the width.
+pub(crate) fn print_table(table_data: &[Vec<String>]) {
+    if table_data.is_empty() {
+        return;
+    }
+
+    // Find the maximum number of columns in any row
+    let num_cols = table_data.iter().map(|row| row.len()).max().unwrap_or(0);
+
+    // Calculate the maximum width of each column
+    let mut column_width = vec![0; num_cols];
+    for row in table_data {
+        for (i, cell) in row.iter().enumerate() {
+            let cell = cell.resetting().to_string();
+            column_width[i] = column_width[i].max(cell.count_characters() + 4);
+        }
+    }
+
+    // Print each row with padded cells for alignment
+    for row in table_data {
+        let mut line = String::new();
+        for (i, cell) in row.iter().enumerate() {
+            let padding = column_width[i] - cell.resetting().to_string().count_characters();
+            line.push_str(&format!("{}{}", cell, " ".repeat(padding)));
+        }
+        print_info!("{}", line);
+    }
+}
+
 fn count_characters(input: &str) -> usize {
-    let mut count = 0;
+    strip_ansi(input).chars().count()
+}
+
+/// Removes the ANSI escape codes, e.g. colors, from the text.
+pub(crate) fn strip_ansi(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
     let mut chars = input.chars().peekable();
 
     while let Some(&ch) = chars.peek() {
@@ -179,12 +213,12 @@ fn count_characters(input: &str) -> usize {
                 }
             }
         } else {
-            count += 1;
+            out.push(ch);
             chars.next();
         }
     }
 
-    count
+    out
 }
 trait LenWithoutControl {
     fn count_characters(&self) -> usize;
@@ -208,6 +242,14 @@ mod tests {
         );
     }
 
+    #[test]
+    fn strip_ansi_test() {
+        assert_eq!(
+            strip_ansi("Avg: 3.4791ms \u{1b}[31m (+18.96%)\u{1b}[0m"),
+            "Avg: 3.4791ms  (+18.96%)"
+        );
+    }
+
     #[test]
     fn test_print_table() {
         let data = vec![
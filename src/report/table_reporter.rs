@@ -1,16 +1,102 @@
 use std::any::Any;
+use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
 
+use prettytable::{Cell, Row, Table, format};
 use yansi::Paint;
 
-use super::{REPORTER_PLUGIN_NAME, avg_median_str, memory_str, min_max_str};
+use super::{
+    REPORTER_PLUGIN_NAME, avg_median_str,
+    criterion_writer::percentile,
+    format::{format_duration, format_with_underscores_f64},
+    markdown_reporter::escape as escape_markdown,
+    memory_str, metadata_diff_str, min_max_str,
+    plain_reporter::strip_ansi,
+    reserve_stdout,
+};
 use crate::{
-    plugins::{EventListener, PluginEvents},
+    bench::BenchResult,
+    plugins::{EventListener, PerfCounter, PerfCounterValues, PluginEvents, all_perf_counters},
     report::{PrintOnce, check_and_print},
+    stats::{compute_percentage_diff, format_percentage},
 };
 
+/// The output mode of the [TableReporter].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TableOutput {
+    /// A table with borders and colored deltas for the terminal.
+    #[default]
+    Terminal,
+    /// A GitHub-flavored markdown table per group, without colors.
+    Markdown,
+    /// CSV with bare values, e.g. for a spreadsheet. Times are in nanoseconds, memory in bytes
+    /// and the deltas are omitted. The runner and group are added as columns. The columns are the
+    /// same for all groups, e.g. a column per perf counter, so the header is printed once per
+    /// process and the tables of all groups and runners form one CSV.
+    Csv,
+}
+
+/// Additional statistics of the samples, which can be added as columns to the [TableReporter].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExtraStat {
+    /// The standard deviation of the time per iteration.
+    StdDev,
+    /// The 95th percentile of the time per iteration.
+    P95,
+    /// The 99th percentile of the time per iteration.
+    P99,
+    /// The number of samples.
+    NumSamples,
+}
+
+impl ExtraStat {
+    fn title(self) -> &'static str {
+        match self {
+            ExtraStat::StdDev => "Std Dev",
+            ExtraStat::P95 => "P95",
+            ExtraStat::P99 => "P99",
+            ExtraStat::NumSamples => "Samples",
+        }
+    }
+
+    /// Returns the statistic in nanoseconds, or the number of samples.
+    fn value(self, durations_ns: &[u64]) -> Option<u64> {
+        if durations_ns.is_empty() {
+            return None;
+        }
+        let mut sorted: Vec<f64> = durations_ns.iter().map(|value| *value as f64).collect();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        let value = match self {
+            ExtraStat::StdDev => {
+                let mean = sorted.iter().sum::<f64>() / sorted.len() as f64;
+                let variance = sorted
+                    .iter()
+                    .map(|value| (value - mean).powi(2))
+                    .sum::<f64>()
+                    / (sorted.len().max(2) - 1) as f64;
+                variance.sqrt()
+            }
+            ExtraStat::P95 => percentile(&sorted, 0.95),
+            ExtraStat::P99 => percentile(&sorted, 0.99),
+            ExtraStat::NumSamples => sorted.len() as f64,
+        };
+        Some(value as u64)
+    }
+
+    fn compute(self, durations_ns: &[u64]) -> String {
+        match (self, self.value(durations_ns)) {
+            (_, None) => String::new(),
+            (ExtraStat::NumSamples, Some(value)) => value.to_string(),
+            (_, Some(value)) => format_duration(value),
+        }
+    }
+}
+
 /// The TableReporter prints the results using prettytable.
 ///
-/// It does not yet conver eveything, it does not report on OutputValue and perf stats.
+/// It reports the same as the [PlainReporter](crate::report::PlainReporter): memory, avg,
+/// median, min .. max, the output value with its delta and a column per perf counter with its
+/// delta. Statistics of the samples can be added with [TableReporter::extra_stat], and the
+/// table can be printed as markdown or CSV with [TableReporter::output].
 ///
 /// ## Example
 /// ```text
@@ -20,20 +106,353 @@ use crate::{
 /// | vec     | Memory: 404 B  | 8.6635 GiB/s (+1.16%) | 8.5639 GiB/s (-1.15%) | [8.7654 GiB/s .. 8.2784 GiB/s] |
 /// | hashmap | Memory: 84 B   | 840.24 MiB/s (+1.54%) | 841.17 MiB/s (+0.33%) | [843.96 MiB/s .. 817.73 MiB/s] |
 /// ```
+///
+/// ## Usage Example
+/// ```rust
+/// use binggan::*;
+/// use binggan::report::{ExtraStat, TableOutput, TableReporter};
+///
+/// let mut runner = BenchRunner::new();
+/// runner.get_plugin_manager().replace_plugin(
+///     TableReporter::new()
+///         .output(TableOutput::Markdown)
+///         .extra_stat(ExtraStat::P99),
+/// );
+/// ```
 #[derive(Clone)]
 pub struct TableReporter {
     print_runner_name_once: Option<PrintOnce>,
+    print_num_iter: bool,
+    output: TableOutput,
+    extra_stats: Vec<ExtraStat>,
+    /// The last printed environment difference, to print it only once.
+    last_metadata_diff: Option<String>,
 }
 
+/// Whether the CSV header was printed. It's shared by all TableReporters, since they print to
+/// the same stdout.
+static PRINTED_CSV_HEADER: AtomicBool = AtomicBool::new(false);
+
 impl TableReporter {
     /// Creates a new TableReporter
     pub fn new() -> Self {
         Self {
             print_runner_name_once: None,
+            print_num_iter: false,
+            output: TableOutput::default(),
+            extra_stats: Vec::new(),
+            last_metadata_diff: None,
+        }
+    }
+
+    /// Print the number of iterations for each benchmark group
+    pub fn print_num_iter(mut self, print: bool) -> Self {
+        self.print_num_iter = print;
+        self
+    }
+
+    /// Sets the output mode, [TableOutput::Terminal] by default.
+    ///
+    /// With markdown or CSV, other output like warnings is printed to stderr.
+    pub fn output(mut self, output: TableOutput) -> Self {
+        if output != TableOutput::Terminal {
+            reserve_stdout();
+        }
+        self.output = output;
+        self
+    }
+
+    /// Adds a column with the statistic, computed from the samples of the bench.
+    pub fn extra_stat(mut self, extra_stat: ExtraStat) -> Self {
+        self.extra_stats.push(extra_stat);
+        self
+    }
+
+    /// Prints informational messages, which are not part of the table.
+    fn print_message(&self, message: &str) {
+        match self.output {
+            TableOutput::Terminal => println!("{}", message),
+            TableOutput::Markdown => {
+                for line in strip_ansi(message).lines() {
+                    println!("> {}", line.trim());
+                }
+                println!();
+            }
+            TableOutput::Csv => eprintln!("{}", message),
+        }
+    }
+
+    /// Returns the titles and rows of the table.
+    fn to_rows(
+        &self,
+        results: &[BenchResult],
+        output_value_column_title: &'static str,
+    ) -> (Vec<String>, Vec<Vec<String>>) {
+        let tracked_memory = results.iter().any(|result| result.tracked_memory);
+        let has_output_value = results.iter().any(|r| r.output_value.is_some());
+        // Benches in a group may have different output types. In that case the title is
+        // added to each cell.
+        let same_column_title = results
+            .iter()
+            .all(|r| r.output_value_column_title == output_value_column_title);
+        let perf_counters: Vec<PerfCounter> = results
+            .iter()
+            .find_map(|result| result.perf_counter.as_ref())
+            .map(|perf_counter| {
+                perf_counter
+                    .values()
+                    .iter()
+                    .map(|(counter, _)| *counter)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let mut titles = vec!["Name".to_string()];
+        if tracked_memory {
+            titles.push("Memory".to_string());
+        }
+        titles.extend(["Avg", "Median", "Min .. Max"].map(str::to_string));
+        titles.extend(self.extra_stats.iter().map(|stat| stat.title().to_string()));
+        if has_output_value {
+            let title = if same_column_title {
+                output_value_column_title
+            } else {
+                "Output"
+            };
+            titles.push(title.to_string());
+        }
+        titles.extend(perf_counters.iter().map(|counter| counter.to_string()));
+
+        let rows = results
+            .iter()
+            .map(|result| {
+                let (avg_str, median_str) =
+                    avg_median_str(&result.stats, result.input_size_in_bytes, result.old_stats);
+                let mut row = vec![result.bench_id.bench_name.clone()];
+                if tracked_memory {
+                    row.push(memory_str(
+                        &result.stats,
+                        result.old_stats,
+                        result.tracked_memory,
+                    ));
+                }
+                row.extend([
+                    avg_str,
+                    median_str,
+                    min_max_str(&result.stats, result.input_size_in_bytes),
+                ]);
+                row.extend(
+                    self.extra_stats
+                        .iter()
+                        .map(|stat| stat.compute(&result.raw_samples.durations_ns)),
+                );
+                if has_output_value {
+                    let output_value = result
+                        .output_value
+                        .as_ref()
+                        .map(|value| {
+                            // Not every value can be formatted as delta
+                            let delta_formatted = result
+                                .output_value_delta
+                                .as_deref()
+                                .map(|delta| format!(" {delta}"))
+                                .unwrap_or_default();
+                            if same_column_title {
+                                format!("{}{delta_formatted}", value)
+                            } else {
+                                format!(
+                                    "{}: {}{delta_formatted}",
+                                    result.output_value_column_title, value
+                                )
+                            }
+                        })
+                        .unwrap_or_default();
+                    row.push(output_value);
+                }
+                row.extend(perf_counters.iter().map(|counter| {
+                    perf_counter_str(
+                        *counter,
+                        result.perf_counter.as_ref(),
+                        result.old_perf_counter.as_ref(),
+                    )
+                }));
+                row
+            })
+            .collect();
+        (titles, rows)
+    }
+
+    /// Returns the titles and rows of the CSV table. The columns are the same for all groups, so
+    /// the rows of all groups fit the same header.
+    fn to_csv_rows(&self, results: &[BenchResult]) -> (Vec<String>, Vec<Vec<String>>) {
+        let perf_counters: Vec<PerfCounter> = all_perf_counters().collect();
+        let mut titles: Vec<String> = ["Name", "Memory", "Avg", "Median", "Min", "Max"]
+            .map(str::to_string)
+            .to_vec();
+        titles.extend(self.extra_stats.iter().map(|stat| stat.title().to_string()));
+        titles.push("Output".to_string());
+        titles.extend(perf_counters.iter().map(|counter| counter.to_string()));
+
+        let rows = results
+            .iter()
+            .map(|result| {
+                let stats = &result.stats;
+                let memory = if result.tracked_memory {
+                    stats.avg_memory.to_string()
+                } else {
+                    String::new()
+                };
+                let mut row = vec![result.bench_id.bench_name.clone(), memory];
+                row.extend(
+                    [
+                        stats.average_ns,
+                        stats.median_ns,
+                        stats.min_ns,
+                        stats.max_ns,
+                    ]
+                    .map(|value| value.to_string()),
+                );
+                row.extend(self.extra_stats.iter().map(|stat| {
+                    stat.value(&result.raw_samples.durations_ns)
+                        .map(|value| value.to_string())
+                        .unwrap_or_default()
+                }));
+                row.push(result.output_value.clone().unwrap_or_default());
+                row.extend(perf_counters.iter().map(|counter| {
+                    result
+                        .perf_counter
+                        .as_ref()
+                        .and_then(|perf_counter| {
+                            perf_counter
+                                .values()
+                                .iter()
+                                .find(|(other, _)| other == counter)
+                                .map(|(_, value)| value.to_string())
+                        })
+                        .unwrap_or_default()
+                }));
+                row
+            })
+            .collect();
+        (titles, rows)
+    }
+
+    fn print_table(
+        &self,
+        runner_name: Option<&str>,
+        group_name: Option<&str>,
+        titles: Vec<String>,
+        rows: Vec<Vec<String>>,
+    ) {
+        let with_csv_header = self.output == TableOutput::Csv
+            && !PRINTED_CSV_HEADER.swap(true, AtomicOrdering::Relaxed);
+        print!(
+            "{}",
+            self.format_table(runner_name, group_name, titles, rows, with_csv_header)
+        );
+    }
+
+    /// Formats the table in the output mode. The CSV header is only added if `with_csv_header`
+    /// is set.
+    fn format_table(
+        &self,
+        runner_name: Option<&str>,
+        group_name: Option<&str>,
+        mut titles: Vec<String>,
+        mut rows: Vec<Vec<String>>,
+        with_csv_header: bool,
+    ) -> String {
+        if self.output != TableOutput::Terminal {
+            for cell in rows.iter_mut().flatten() {
+                *cell = strip_ansi(cell).trim().to_string();
+            }
+        }
+        if self.output == TableOutput::Markdown {
+            for cell in titles.iter_mut().chain(rows.iter_mut().flatten()) {
+                *cell = escape_markdown(cell);
+            }
+        }
+        if self.output == TableOutput::Csv {
+            titles.splice(0..0, ["Runner".to_string(), "Group".to_string()]);
+            for row in rows.iter_mut() {
+                let names = [runner_name, group_name].map(|name| name.unwrap_or_default());
+                row.splice(0..0, names.map(str::to_string));
+            }
+        }
+
+        let mut table = Table::new();
+        for row in rows {
+            table.add_row(Row::new(row.iter().map(|cell| Cell::new(cell)).collect()));
+        }
+        let titles = Row::new(titles.iter().map(|title| Cell::new(title)).collect());
+        match self.output {
+            TableOutput::Terminal | TableOutput::Markdown => {
+                // Markdown tables only differ in the junctions of the title separator
+                let junction = if self.output == TableOutput::Markdown {
+                    '|'
+                } else {
+                    '+'
+                };
+                table.set_format(
+                    format::FormatBuilder::new()
+                        .column_separator('|')
+                        .borders('|')
+                        .separators(
+                            &[format::LinePosition::Title],
+                            format::LineSeparator::new('-', junction, junction, junction),
+                        )
+                        .padding(1, 1)
+                        .build(),
+                );
+                table.set_titles(titles);
+                let mut out = table.to_string();
+                if self.output == TableOutput::Markdown {
+                    out.push('\n');
+                }
+                out
+            }
+            TableOutput::Csv => {
+                if with_csv_header {
+                    table.set_titles(titles);
+                }
+                let mut out = Vec::new();
+                if let Err(err) = table.to_csv(&mut out) {
+                    eprintln!("{} Could not print the table: {}", "Warning:".yellow(), err);
+                }
+                String::from_utf8_lossy(&out).to_string()
+            }
         }
     }
 }
 
+/// The value of the counter per iteration and the delta to the old value.
+fn perf_counter_str(
+    counter: PerfCounter,
+    perf_counter: Option<&PerfCounterValues>,
+    old_perf_counter: Option<&PerfCounterValues>,
+) -> String {
+    let value = |perf_counter: Option<&PerfCounterValues>| {
+        perf_counter?
+            .values()
+            .iter()
+            .find(|(other, _)| *other == counter)
+            .map(|(_, value)| *value)
+    };
+    let Some(new) = value(perf_counter) else {
+        return String::new();
+    };
+    let diff_str = value(old_perf_counter)
+        .filter(|old| *old != 0.0 && new != 0.0 && *old != new)
+        .map(|old| {
+            format!(
+                " {}",
+                format_percentage(compute_percentage_diff(new, old), true)
+            )
+        })
+        .unwrap_or_default();
+    format!("{}{}", format_with_underscores_f64(new), diff_str)
+}
+
 impl Default for TableReporter {
     fn default() -> Self {
         Self::new()
@@ -50,83 +469,160 @@ impl EventListener for TableReporter {
     fn on_event(&mut self, event: PluginEvents) {
         match event {
             PluginEvents::BenchStart { bench_id: _ } => {}
+            PluginEvents::NoiseFindings { findings } => {
+                for finding in findings {
+                    self.print_message(&format!("{} {}", "Warning:".yellow().bold(), finding));
+                }
+            }
+            PluginEvents::InputBuilt {
+                input_name,
+                duration,
+            } => {
+                self.print_message(&format!(
+                    "Built input {} in {}",
+                    input_name,
+                    format_duration(duration)
+                ));
+            }
             PluginEvents::GroupStart {
                 runner_name,
                 group_name,
                 output_value_column_title: _,
-            } => {
-                if let Some(runner_name) = runner_name {
-                    check_and_print(&mut self.print_runner_name_once, runner_name);
+            } => match self.output {
+                TableOutput::Terminal => {
+                    if let Some(runner_name) = runner_name {
+                        check_and_print(&mut self.print_runner_name_once, runner_name);
+                    }
+                    if let Some(group_name) = group_name {
+                        println!("{}", group_name.black().on_yellow().invert().bold());
+                    }
                 }
-                if let Some(group_name) = group_name {
-                    println!("{}", group_name.black().on_yellow().invert().bold());
+                TableOutput::Markdown => {
+                    let title = [runner_name, group_name]
+                        .into_iter()
+                        .flatten()
+                        .collect::<Vec<_>>()
+                        .join(" / ");
+                    if !title.is_empty() {
+                        println!("### {}\n", escape_markdown(&title));
+                    }
                 }
+                TableOutput::Csv => {}
+            },
+            PluginEvents::GroupBenchNumIters { num_iter } if self.print_num_iter => {
+                self.print_message(&format!("Num Iter Benches in Group {}", num_iter.bold()));
+            }
+            PluginEvents::GroupNumIters { num_iter } if self.print_num_iter => {
+                self.print_message(&format!("Num Iter Group {}", num_iter.bold()));
             }
             PluginEvents::GroupStop {
-                runner_name: _,
-                group_name: _,
+                runner_name,
+                group_name,
                 results,
                 output_value_column_title,
             } => {
-                use prettytable::*;
-                let mut table = Table::new();
-                let format = format::FormatBuilder::new()
-                    .column_separator('|')
-                    .borders('|')
-                    .separators(
-                        &[format::LinePosition::Title],
-                        format::LineSeparator::new('-', '+', '+', '+'),
-                    )
-                    .padding(1, 1)
-                    .build();
-                table.set_format(format);
-
-                let mut row = prettytable::row!["Name", "Memory", "Avg", "Median", "Min .. Max"];
-                if !results[0].tracked_memory {
-                    row.remove_cell(1);
-                }
-                let has_output_value = results.iter().any(|r| r.output_value.is_some());
-                if has_output_value {
-                    row.add_cell(Cell::new(output_value_column_title));
-                }
-                table.set_titles(row);
-                for result in results {
-                    let (avg_str, median_str) =
-                        avg_median_str(&result.stats, result.input_size_in_bytes, result.old_stats);
-                    let min_max = min_max_str(&result.stats, result.input_size_in_bytes);
-                    let memory_string =
-                        memory_str(&result.stats, result.old_stats, result.tracked_memory);
-                    let mut row = Row::new(vec![
-                        Cell::new(&result.bench_id.bench_name),
-                        Cell::new(&memory_string),
-                        Cell::new(&avg_str),
-                        Cell::new(&median_str),
-                        Cell::new(&min_max),
-                    ]);
-                    if has_output_value {
-                        let output_value = result
-                            .output_value
-                            .as_ref()
-                            .map(|value| {
-                                // Not every value can be formatted as delta
-                                let delta_formatted = result
-                                    .output_value_delta
-                                    .as_deref()
-                                    .map(|delta| format!(" {delta}"))
-                                    .unwrap_or_default();
-                                format!("{}{delta_formatted}", value,)
-                            })
-                            .unwrap_or_default();
-                        row.add_cell(Cell::new(&output_value));
-                    }
-                    if !result.tracked_memory {
-                        row.remove_cell(1);
+                if let Some(diff) = metadata_diff_str(results) {
+                    if self.last_metadata_diff.as_ref() != Some(&diff) {
+                        self.print_message(&diff.yellow().to_string());
+                        self.last_metadata_diff = Some(diff);
                     }
-                    table.add_row(row);
                 }
-                table.printstd();
+                let (titles, rows) = if self.output == TableOutput::Csv {
+                    self.to_csv_rows(results)
+                } else {
+                    self.to_rows(results, output_value_column_title)
+                };
+                self.print_table(runner_name, group_name, titles, rows);
             }
             _ => {}
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(bench_name: &str) -> BenchResult {
+        BenchResult::for_test(bench_name, 3)
+    }
+
+    #[test]
+    fn extra_stats_test() {
+        let durations_ns = [1, 2, 3, 4, 5];
+        assert_eq!(ExtraStat::NumSamples.compute(&durations_ns), "5");
+        assert_eq!(ExtraStat::P95.compute(&durations_ns), "4ns");
+        assert_eq!(ExtraStat::StdDev.compute(&durations_ns), "1ns");
+        assert_eq!(ExtraStat::P99.compute(&[]), "");
+    }
+
+    #[test]
+    fn rows_test() {
+        let reporter = TableReporter::new().extra_stat(ExtraStat::NumSamples);
+        let results = [
+            BenchResult {
+                output_value: Some("42".to_string()),
+                output_value_delta: Some("(+5.00%)".to_string()),
+                ..result("a")
+            },
+            result("b"),
+        ];
+        let (titles, rows) = reporter.to_rows(&results, "Output");
+        assert_eq!(
+            titles,
+            vec!["Name", "Avg", "Median", "Min .. Max", "Samples", "Output"]
+        );
+        assert_eq!(rows[0][0], "a");
+        assert_eq!(rows[0][4], "3");
+        assert_eq!(rows[0][5], "42 (+5.00%)");
+        assert_eq!(rows[1][5], "");
+    }
+
+    #[test]
+    fn markdown_output_is_escaped() {
+        let reporter = TableReporter::new().output(TableOutput::Markdown);
+        let results = [BenchResult {
+            output_value: Some("a\nb".to_string()),
+            ..result("x|y")
+        }];
+        let (titles, rows) = reporter.to_rows(&results, "Output");
+        let table = reporter.format_table(None, None, titles, rows, false);
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines.len(), 4, "{}", table);
+        assert!(lines[2].starts_with("| x\\|y "), "{}", table);
+        assert!(lines[2].contains(" a b "), "{}", table);
+        // Every line has the same number of columns
+        let num_separators = |line: &str| line.replace("\\|", "").matches('|').count();
+        assert!(
+            lines[..3].iter().all(|line| num_separators(line) == 6),
+            "{}",
+            table
+        );
+    }
+
+    #[test]
+    fn csv_columns_are_fixed() {
+        let reporter = TableReporter::new().output(TableOutput::Csv);
+        let (titles, rows) = reporter.to_csv_rows(&[result("a")]);
+        let table = reporter.format_table(Some("runner"), Some("group"), titles, rows, true);
+        let mut lines = table.lines();
+        assert_eq!(
+            lines.next().unwrap(),
+            "Runner,Group,Name,Memory,Avg,Median,Min,Max,Output,Cycles,Br,BrM,L1dA,L1dM,dTLBA,\
+             dTLBM,IRet,PGF,PGFMin,PGFMaj"
+        );
+        assert_eq!(lines.next().unwrap(), "runner,group,a,,3,3,1,6,,,,,,,,,,,,");
+
+        // Other groups have the same columns, the header is only printed once
+        let results = [BenchResult {
+            tracked_memory: true,
+            output_value: Some("42".to_string()),
+            output_value_column_title: "Size",
+            ..result("b")
+        }];
+        let (titles, rows) = reporter.to_csv_rows(&results);
+        assert_eq!(titles.len(), 18);
+        let table = reporter.format_table(None, None, titles, rows, false);
+        assert_eq!(table, ",,b,0,3,3,1,6,42,,,,,,,,,,,\n");
+    }
+}
@@ -1,4 +1,5 @@
 use crate::bench::RunResult;
+use crate::plugins::PerfCounterValues;
 use miniserde::{Deserialize, Serialize};
 use yansi::Paint;
 
@@ -24,6 +25,22 @@ pub struct BenchStats {
     pub avg_memory: usize,
 }
 
+/// The raw measurements of a bench, one value per group iteration.
+///
+/// They are stored in the result file if
+/// [Config::store_raw_samples](crate::Config::store_raw_samples) is enabled.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RawSamples {
+    /// The time of a single iteration, in nanoseconds.
+    pub durations_ns: Vec<u64>,
+    /// The perf counter values of a single iteration. Empty without the
+    /// [PerfCounterPlugin](crate::plugins::PerfCounterPlugin).
+    pub perf_counter: Vec<PerfCounterValues>,
+    /// The peak memory consumption, in bytes. Empty without the
+    /// [PeakMemAllocPlugin](crate::plugins::PeakMemAllocPlugin).
+    pub memory: Vec<usize>,
+}
+
 /// Compute diff from two values of BenchStats
 pub fn compute_diff<F: Fn(&BenchStats) -> u64>(
     stats: &BenchStats,
@@ -89,16 +106,25 @@ pub fn format_percentage(diff: f64, smaller_is_better: bool) -> String {
 pub fn compute_stats<O>(
     results: &[RunResult<O>],
     memory_consumption: Option<&Vec<usize>>,
+) -> BenchStats {
+    let durations_ns: Vec<u64> = results.iter().map(|res| res.duration_ns).collect();
+    compute_stats_from_durations(durations_ns, memory_consumption)
+}
+
+/// Computes the stats from the time of each group iteration and the peak memory.
+pub(crate) fn compute_stats_from_durations(
+    mut sorted_results: Vec<u64>,
+    memory_consumption: Option<&Vec<usize>>,
 ) -> BenchStats {
     // Avg memory consumption
     let avg_memory = memory_consumption
+        .filter(|memory_consumption| !memory_consumption.is_empty())
         .map(|memory_consumption| {
             let total_memory: usize = memory_consumption.iter().copied().sum();
             total_memory / memory_consumption.len()
         })
         .unwrap_or(0);
 
-    let mut sorted_results: Vec<u64> = results.iter().map(|res| res.duration_ns).collect();
     sorted_results.sort();
 
     // Calculate minimum and maximum
@@ -1,6 +1,23 @@
-use std::{env, path::PathBuf, sync::OnceLock};
+use std::{
+    env,
+    path::{Path, PathBuf},
+    sync::{
+        OnceLock,
+        atomic::{AtomicUsize, Ordering},
+    },
+};
 
-use crate::{bench::BenchResult, bench_id::BenchId, plugins::PerfCounterValues, stats::BenchStats};
+use miniserde::{Deserialize, Serialize};
+use yansi::Paint;
+
+use crate::{
+    RunMetadata,
+    bench::BenchResult,
+    bench_id::BenchId,
+    plugins::PerfCounterValues,
+    report::print_info,
+    stats::{BenchStats, RawSamples},
+};
 
 /// Creates directory if it does not exist
 pub fn get_output_directory() -> &'static PathBuf {
@@ -20,52 +37,591 @@ pub fn get_output_directory() -> &'static PathBuf {
     })
 }
 
-fn get_bench_file(bench_id: &BenchId) -> PathBuf {
-    get_output_directory().join(bench_id.get_full_name())
+/// Returns the name of the bench binary without the hash suffix added by cargo, e.g. `my_bench`
+/// for `target/release/deps/my_bench-1a2b3c4d5e6f7a8b`.
+///
+/// The result files are namespaced by the binary, so runners with the same name in different
+/// bench binaries, which may also run concurrently, don't overwrite each other's results.
+fn get_binary_name() -> &'static str {
+    static BINARY_NAME: OnceLock<String> = OnceLock::new();
+    BINARY_NAME.get_or_init(|| {
+        let file_stem = env::current_exe()
+            .ok()
+            .and_then(|path| {
+                path.file_stem()
+                    .map(|stem| stem.to_string_lossy().to_string())
+            })
+            .unwrap_or_default();
+        strip_cargo_hash(&file_stem).to_string()
+    })
+}
+
+fn strip_cargo_hash(file_stem: &str) -> &str {
+    match file_stem.rsplit_once('-') {
+        Some((name, hash)) if hash.len() == 16 && hash.chars().all(|c| c.is_ascii_hexdigit()) => {
+            name
+        }
+        _ => file_stem,
+    }
+}
+
+/// The maximum length of the readable part of a file name. Together with the binary name and
+/// the hash it stays well below the limit of 255 bytes of common filesystems.
+const MAX_READABLE_NAME_LEN: usize = 100;
+
+/// Returns the file name of the bench: the name of the bench binary, the full name of the bench
+/// id and a hash of the bench id, e.g. `my_bench.runner_group_bench.3f2a6c1b0e9d8a7f`.
+///
+/// The name is escaped and length-limited, so it is valid on all common filesystems. Characters
+/// other than ASCII alphanumerics, `-` and `_` are replaced by `_`. Since this is lossy, the hash
+/// keeps the names of different ids distinct. The readable id is stored inside the file.
+pub(crate) fn get_bench_file_name(bench_id: &BenchId) -> String {
+    bench_file_name(get_binary_name(), bench_id)
+}
+
+fn bench_file_name(binary_name: &str, bench_id: &BenchId) -> String {
+    let mut readable_name = escape_file_name(&bench_id.get_full_name());
+    readable_name.truncate(MAX_READABLE_NAME_LEN);
+    let name = format!("{}.{:016x}", readable_name, hash_bench_id(bench_id));
+    if binary_name.is_empty() {
+        name
+    } else {
+        format!("{}.{}", escape_file_name(binary_name), name)
+    }
+}
+
+/// A hash of the bench id, which is stable across platforms and versions (64-bit FNV-1a).
+///
+/// The parts are length-prefixed, so e.g. `a_b` + `c` and `a` + `b_c` hash differently.
+fn hash_bench_id(bench_id: &BenchId) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    let mut write = |bytes: &[u8]| {
+        for byte in bytes {
+            hash ^= u64::from(*byte);
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    };
+    let parts = [
+        bench_id.runner_name.as_deref(),
+        bench_id.group_name.as_deref(),
+        Some(bench_id.bench_name.as_str()),
+    ];
+    for part in parts {
+        match part {
+            Some(part) => {
+                write(&[1]);
+                write(&(part.len() as u64).to_le_bytes());
+                write(part.as_bytes());
+            }
+            None => write(&[0]),
+        }
+    }
+    hash
+}
+
+/// Returns the file of the bench. Named baselines are stored in a subdirectory with the name of
+/// the baseline, the last run directly in the output directory.
+fn get_bench_file(bench_id: &BenchId, baseline: Option<&str>) -> PathBuf {
+    get_results_directory(baseline).join(get_bench_file_name(bench_id))
+}
+
+fn get_results_directory(baseline: Option<&str>) -> PathBuf {
+    match baseline {
+        Some(baseline) => get_baseline_directory(baseline),
+        None => get_output_directory().clone(),
+    }
+}
+
+/// Moves the file of older versions of binggan, which was not namespaced by the bench binary,
+/// to `filepath`, unless `filepath` already exists.
+pub(crate) fn migrate_unnamespaced_file(legacy_filepath: &Path, filepath: &Path) {
+    if legacy_filepath != filepath && !filepath.exists() && legacy_filepath.is_file() {
+        // Another process may migrate the same file concurrently, then the rename fails
+        let _ = std::fs::rename(legacy_filepath, filepath);
+    }
+}
+
+/// Returns the directory of the named baseline.
+///
+/// The name is escaped, so e.g. `../x` or an absolute path can't point outside the baselines
+/// directory.
+pub(crate) fn get_baseline_directory(baseline: &str) -> PathBuf {
+    get_output_directory()
+        .join(BASELINES_DIRECTORY)
+        .join(escape_file_name(baseline))
+}
+
+fn escape_file_name(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
 }
 
+/// The subdirectory of the output directory, which contains the named baselines.
+const BASELINES_DIRECTORY: &str = "baselines";
+
 pub(crate) struct PreviousRun {
     pub stats: BenchStats,
     pub perf_counter: Option<PerfCounterValues>,
     pub serialized_output_value: Option<String>,
+    pub metadata: Option<RunMetadata>,
+    pub raw_samples: Option<RawSamples>,
 }
 
-pub(crate) fn fetch_previous_run(bench_id: &BenchId) -> Option<PreviousRun> {
-    // Filepath in target directory
-    let filepath = get_bench_file(bench_id);
-    // Check if file exists and deserialize
-    if filepath.exists() {
-        let content = std::fs::read_to_string(&filepath).unwrap();
-        let lines: Vec<_> = content.lines().collect();
-        let stats = miniserde::json::from_str(lines[0]).unwrap();
-        let perf_counter = lines
-            .get(1)
-            .and_then(|line| miniserde::json::from_str(line).ok());
-        let serialized_output_value = lines.get(2).map(|line| (*line).to_string());
-        return Some(PreviousRun {
-            stats,
-            perf_counter,
-            serialized_output_value,
-        });
-    }
-    None
-}
-
-pub(crate) fn write_results_to_disk(result: &BenchResult) {
-    let mut out = miniserde::json::to_string(&result.stats);
-    if let Some(perf_counter) = &result.perf_counter {
-        out.push('\n');
-        let perf_out = miniserde::json::to_string(perf_counter);
-        out.push_str(&perf_out);
-    }
-    if let Some(output_value) = &result.serialized_output_value {
-        if result.perf_counter.is_none() {
-            // Keep the second line reserved for perf counters so old readers still either read
-            // perf counters there or ignore the empty line.
-            out.push('\n');
+/// The version of the result file format. Files without a version use the line-based format of
+/// older versions of binggan and are migrated when they are read.
+const FORMAT_VERSION: u32 = 1;
+
+/// The results of a bench, stored as a single JSON document per bench.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub(crate) struct ResultFile {
+    pub version: u32,
+    /// Missing in files of older versions.
+    pub bench_id: Option<BenchId>,
+    pub stats: BenchStats,
+    pub perf_counter: Option<PerfCounterValues>,
+    pub output_value: Option<String>,
+    pub noise_findings: Option<Vec<String>>,
+    pub metadata: Option<RunMetadata>,
+    pub raw_samples: Option<RawSamples>,
+}
+
+impl ResultFile {
+    fn from_result(result: &BenchResult, store_raw_samples: bool) -> Self {
+        let noise_findings: Vec<String> = result
+            .noise_findings
+            .iter()
+            .map(|finding| finding.to_string())
+            .collect();
+        ResultFile {
+            version: FORMAT_VERSION,
+            bench_id: Some(result.bench_id.clone()),
+            stats: result.stats,
+            perf_counter: result.perf_counter.clone(),
+            output_value: result.serialized_output_value.clone(),
+            noise_findings: (!noise_findings.is_empty()).then_some(noise_findings),
+            metadata: result.metadata.clone(),
+            raw_samples: store_raw_samples.then(|| result.raw_samples.clone()),
+        }
+    }
+
+    /// Parses the line-based format of older versions: stats, perf counters and output value,
+    /// each on its own line.
+    fn parse_legacy(content: &str) -> Option<Self> {
+        let lines: Vec<&str> = content.lines().collect();
+        let non_empty = |idx: usize| lines.get(idx).copied().filter(|line| !line.is_empty());
+        Some(ResultFile {
+            version: FORMAT_VERSION,
+            bench_id: None,
+            stats: miniserde::json::from_str(non_empty(0)?).ok()?,
+            perf_counter: non_empty(1).and_then(|line| miniserde::json::from_str(line).ok()),
+            output_value: non_empty(2).map(str::to_string),
+            noise_findings: None,
+            metadata: None,
+            raw_samples: None,
+        })
+    }
+}
+
+/// Reads the result file. Returns `Ok(None)` if it doesn't exist. Files in the old line-based
+/// format are migrated.
+fn read_result_file(filepath: &Path) -> Result<Option<ResultFile>, String> {
+    let Some((result_file, is_legacy)) = parse_result_file(filepath)? else {
+        return Ok(None);
+    };
+    if is_legacy {
+        // Migration is best effort, the file is read again on the next run otherwise
+        let _ = write_atomic(filepath, &miniserde::json::to_string(&result_file));
+    }
+    Ok(Some(result_file))
+}
+
+/// Reads the result file without modifying it. Returns `Ok(None)` if it doesn't exist. The
+/// returned flag is set if the file is in the old line-based format.
+fn parse_result_file(filepath: &Path) -> Result<Option<(ResultFile, bool)>, String> {
+    let content = match std::fs::read_to_string(filepath) {
+        Ok(content) => content,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err.to_string()),
+    };
+    if let Ok(result_file) = miniserde::json::from_str::<ResultFile>(&content) {
+        if result_file.version > FORMAT_VERSION {
+            return Err(format!(
+                "format version {} is newer than the supported version {}",
+                result_file.version, FORMAT_VERSION
+            ));
         }
-        out.push('\n');
-        out.push_str(output_value);
+        return Ok(Some((result_file, false)));
+    }
+    let result_file = ResultFile::parse_legacy(&content).ok_or("invalid format")?;
+    Ok(Some((result_file, true)))
+}
+
+/// Writes the file via a temporary file and a rename, so readers never see a partial file.
+fn write_atomic(filepath: &Path, content: &str) -> std::io::Result<()> {
+    if let Some(parent) = filepath.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let file_name = filepath
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    // The temporary file is unique per process and write, so concurrent writers of the same
+    // file, e.g. bench binaries sharing the target directory, never write to the same file
+    static NUM_WRITES: AtomicUsize = AtomicUsize::new(0);
+    let tmp_path = filepath.with_file_name(format!(
+        ".{}.{}.{}.tmp",
+        file_name,
+        std::process::id(),
+        NUM_WRITES.fetch_add(1, Ordering::Relaxed)
+    ));
+    std::fs::write(&tmp_path, content)
+        .and_then(|_| std::fs::rename(&tmp_path, filepath))
+        .inspect_err(|_| {
+            let _ = std::fs::remove_file(&tmp_path);
+        })
+}
+
+fn warn(message: std::fmt::Arguments) {
+    print_info!("{} {}", "Warning:".yellow().bold(), message);
+}
+
+/// Reads the results of the last run, or of the named baseline if `baseline` is set.
+///
+/// Unreadable files are ignored with a warning. Files of older versions of binggan are migrated
+/// to the current file name. Files of a different bench id, e.g. of an id that had the same
+/// file name in older versions, are ignored.
+pub(crate) fn fetch_previous_run(
+    bench_id: &BenchId,
+    baseline: Option<&str>,
+) -> Option<PreviousRun> {
+    let filepath = get_bench_file(bench_id, baseline);
+    migrate_unnamespaced_file(
+        &get_results_directory(baseline).join(bench_id.get_full_name()),
+        &filepath,
+    );
+    match read_result_file(&filepath) {
+        Ok(result_file) => result_file
+            .filter(|result_file| {
+                result_file
+                    .bench_id
+                    .as_ref()
+                    .is_none_or(|id| id == bench_id)
+            })
+            .map(|result_file| PreviousRun {
+                stats: result_file.stats,
+                perf_counter: result_file.perf_counter,
+                serialized_output_value: result_file.output_value,
+                metadata: result_file.metadata,
+                raw_samples: result_file.raw_samples,
+            }),
+        Err(err) => {
+            warn(format_args!(
+                "Ignoring results in {}: {}",
+                filepath.display(),
+                err
+            ));
+            None
+        }
+    }
+}
+
+/// A result read from a results directory, see [read_results_in_directory].
+#[cfg(feature = "cli")]
+#[derive(Debug, Clone)]
+pub(crate) struct SavedResult {
+    pub bench_id: BenchId,
+    /// The name of the file, which is namespaced by the bench binary.
+    pub file_name: String,
+    pub file: ResultFile,
+}
+
+#[cfg(all(test, feature = "cli"))]
+impl SavedResult {
+    /// Creates a result with only stats, used in tests.
+    pub(crate) fn for_test(bench_id: BenchId, stats: BenchStats) -> Self {
+        SavedResult {
+            file: ResultFile {
+                version: FORMAT_VERSION,
+                bench_id: Some(bench_id.clone()),
+                stats,
+                perf_counter: None,
+                output_value: None,
+                noise_findings: None,
+                metadata: None,
+                raw_samples: None,
+            },
+            file_name: bench_id.get_full_name(),
+            bench_id,
+        }
+    }
+}
+
+/// Reads the results of all benches in the directory, e.g. the output directory for the last
+/// run or the directory of a named baseline. Files which are not result files are skipped.
+///
+/// The directory is not modified, i.e. files in the old line-based format are not migrated.
+#[cfg(feature = "cli")]
+pub(crate) fn read_results_in_directory(directory: &Path) -> std::io::Result<Vec<SavedResult>> {
+    let mut results = Vec::new();
+    for entry in std::fs::read_dir(directory)? {
+        let path = entry?.path();
+        let file_name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        if !path.is_file() || file_name.starts_with('.') {
+            continue;
+        }
+        if let Ok(Some((result_file, _))) = parse_result_file(&path) {
+            results.push(SavedResult {
+                // Older files don't contain the id, the file name is the full name
+                bench_id: result_file
+                    .bench_id
+                    .clone()
+                    .unwrap_or_else(|| BenchId::from_bench_name(file_name.as_str())),
+                file_name,
+                file: result_file,
+            });
+        }
+    }
+    Ok(results)
+}
+
+/// Writes the result file with the given file name as the named baseline.
+#[cfg(feature = "cli")]
+pub(crate) fn write_baseline(
+    file_name: &str,
+    baseline: &str,
+    result_file: &ResultFile,
+) -> std::io::Result<()> {
+    write_atomic(
+        &get_baseline_directory(baseline).join(file_name),
+        &miniserde::json::to_string(result_file),
+    )
+}
+
+/// Writes the results as the last run, and additionally as the named baseline if
+/// `save_baseline` is set. The raw samples are only written if `store_raw_samples` is set.
+pub(crate) fn write_results_to_disk(
+    result: &BenchResult,
+    save_baseline: Option<&str>,
+    store_raw_samples: bool,
+) {
+    let content = miniserde::json::to_string(&ResultFile::from_result(result, store_raw_samples));
+    let baselines = std::iter::once(None).chain(save_baseline.map(Some));
+    for baseline in baselines {
+        let filepath = get_bench_file(&result.bench_id, baseline);
+        if let Err(err) = write_atomic(&filepath, &content) {
+            warn(format_args!(
+                "Could not write results to {}: {}",
+                filepath.display(),
+                err
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn baselines_are_stored_in_subdirectory() {
+        let bench_id = BenchId::from_bench_name("bench").group_name(Some("group".to_string()));
+        let last_run = get_bench_file(&bench_id, None);
+        let baseline = get_bench_file(&bench_id, Some("main"));
+        assert_eq!(last_run.parent().unwrap(), get_output_directory());
+        assert_eq!(
+            baseline,
+            get_output_directory()
+                .join("baselines")
+                .join("main")
+                .join(get_bench_file_name(&bench_id))
+        );
+        for name in ["../../x", "/tmp/x", "a/b", ".."] {
+            let file = get_bench_file(&bench_id, Some(name));
+            assert_eq!(
+                file.parent().unwrap().parent().unwrap(),
+                get_output_directory().join("baselines")
+            );
+        }
+    }
+
+    #[test]
+    fn bench_files_are_namespaced_by_binary() {
+        assert_eq!(strip_cargo_hash("my_bench-1a2b3c4d5e6f7a8b"), "my_bench");
+        assert_eq!(strip_cargo_hash("my-bench"), "my-bench");
+        let bench_id = BenchId::from_bench_name("bench").runner_name(Some("runner"));
+        let file_name = bench_file_name("my_bench", &bench_id);
+        assert!(file_name.starts_with("my_bench.runner__bench."));
+        assert_eq!(
+            bench_file_name("", &bench_id),
+            file_name.trim_start_matches("my_bench.")
+        );
+        assert_ne!(
+            bench_file_name("a", &bench_id),
+            bench_file_name("b", &bench_id)
+        );
+    }
+
+    #[test]
+    fn bench_file_names_are_safe_and_distinct() {
+        let bench_id = |group: &str, bench: &str| {
+            BenchId::from_bench_name(bench).group_name(Some(group.to_string()))
+        };
+        let file_name = bench_file_name("", &bench_id("a:b\\c\0d", "größe/ü"));
+        let (readable_name, hash) = file_name.rsplit_once('.').unwrap();
+        assert_eq!(readable_name, "_a_b_c_d_gr__e-_");
+        assert_eq!(hash.len(), 16);
+
+        assert_ne!(
+            bench_file_name("", &bench_id("a_b", "c")),
+            bench_file_name("", &bench_id("a", "b_c"))
+        );
+        assert_ne!(
+            bench_file_name("", &BenchId::from_bench_name("a")),
+            bench_file_name("", &bench_id("", "a"))
+        );
+        // The hash is stable across versions, so existing files are found
+        assert_eq!(
+            bench_file_name("", &bench_id("group", "bench")),
+            "_group_bench.0b9db5f05128889e"
+        );
+
+        let long_name = "x".repeat(1000);
+        let file_name = bench_file_name("my_bench", &bench_id("group", &long_name));
+        assert!(file_name.len() < 150);
+        assert_ne!(
+            file_name,
+            bench_file_name("my_bench", &bench_id("group", &format!("{}y", long_name)))
+        );
+    }
+
+    #[test]
+    fn migrate_unnamespaced_result_file() {
+        let dir = test_dir("unnamespaced");
+        let legacy = dir.join("_group_bench");
+        let namespaced = dir.join("my_bench._group_bench.0b9db5f05128889e");
+        std::fs::write(&legacy, "legacy").unwrap();
+        migrate_unnamespaced_file(&legacy, &namespaced);
+        assert!(!legacy.exists());
+        assert_eq!(std::fs::read_to_string(&namespaced).unwrap(), "legacy");
+
+        // An existing namespaced file is never replaced
+        std::fs::write(&legacy, "other binary").unwrap();
+        migrate_unnamespaced_file(&legacy, &namespaced);
+        assert_eq!(std::fs::read_to_string(&namespaced).unwrap(), "legacy");
+    }
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("binggan_write_results_{}", name));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn migrate_legacy_result_file() {
+        let filepath = test_dir("legacy").join("bench");
+        let stats = r#"{"min_ns":1,"max_ns":3,"average_ns":2,"median_ns":2,"avg_memory":0}"#;
+        std::fs::write(&filepath, format!("{}\n\n150", stats)).unwrap();
+        let result_file = read_result_file(&filepath).unwrap().unwrap();
+        assert_eq!(result_file.stats.median_ns, 2);
+        assert!(result_file.perf_counter.is_none());
+        assert_eq!(result_file.output_value.as_deref(), Some("150"));
+
+        // The file was rewritten in the versioned format
+        let content = std::fs::read_to_string(&filepath).unwrap();
+        assert!(content.starts_with(r#"{"version":1,"#));
+        let result_file = read_result_file(&filepath).unwrap().unwrap();
+        assert_eq!(result_file.output_value.as_deref(), Some("150"));
+    }
+
+    #[test]
+    fn unreadable_result_files_are_errors() {
+        let dir = test_dir("unreadable");
+        assert!(read_result_file(&dir.join("missing")).unwrap().is_none());
+
+        let corrupt = dir.join("corrupt");
+        std::fs::write(&corrupt, "{\"min_ns\":").unwrap();
+        assert!(read_result_file(&corrupt).is_err());
+
+        let newer = dir.join("newer");
+        let stats = r#"{"min_ns":1,"max_ns":3,"average_ns":2,"median_ns":2,"avg_memory":0}"#;
+        std::fs::write(&newer, format!(r#"{{"version":99,"stats":{}}}"#, stats)).unwrap();
+        assert!(read_result_file(&newer).is_err());
+    }
+
+    #[test]
+    fn raw_samples_roundtrip() {
+        let filepath = test_dir("raw_samples").join("bench");
+        let stats = r#"{"min_ns":1,"max_ns":3,"average_ns":2,"median_ns":2,"avg_memory":0}"#;
+        let raw_samples = r#"{"durations_ns":[1,3,2],"perf_counter":[],"memory":[10,20,30]}"#;
+        let content = format!(
+            r#"{{"version":1,"stats":{},"raw_samples":{}}}"#,
+            stats, raw_samples
+        );
+        std::fs::write(&filepath, content).unwrap();
+        let result_file = read_result_file(&filepath).unwrap().unwrap();
+        let raw_samples = result_file.raw_samples.unwrap();
+        assert_eq!(raw_samples.durations_ns, vec![1, 3, 2]);
+        assert_eq!(raw_samples.memory, vec![10, 20, 30]);
+        assert!(raw_samples.perf_counter.is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "cli")]
+    fn read_results_directory() {
+        let dir = test_dir("directory");
+        let stats = r#"{"min_ns":1,"max_ns":3,"average_ns":2,"median_ns":2,"avg_memory":0}"#;
+        let bench_id = r#"{"runner_name":null,"group_name":"group","bench_name":"bench"}"#;
+        std::fs::write(
+            dir.join("with_id"),
+            format!(
+                r#"{{"version":1,"bench_id":{},"stats":{}}}"#,
+                bench_id, stats
+            ),
+        )
+        .unwrap();
+        std::fs::write(dir.join("_group_legacy"), stats).unwrap();
+        std::fs::write(dir.join("results.csv"), "runner,group").unwrap();
+        std::fs::create_dir_all(dir.join("history")).unwrap();
+
+        let mut names: Vec<String> = read_results_in_directory(&dir)
+            .unwrap()
+            .into_iter()
+            .map(|result| {
+                format!(
+                    "{:?}/{}",
+                    result.bench_id.group_name, result.bench_id.bench_name
+                )
+            })
+            .collect();
+        names.sort();
+        assert_eq!(names, vec!["None/_group_legacy", "Some(\"group\")/bench"]);
+        // Reading doesn't migrate
+        assert_eq!(
+            std::fs::read_to_string(dir.join("_group_legacy")).unwrap(),
+            stats
+        );
+    }
+
+    #[test]
+    fn write_atomic_replaces_file() {
+        let filepath = test_dir("atomic").join("nested").join("bench");
+        write_atomic(&filepath, "a").unwrap();
+        write_atomic(&filepath, "b").unwrap();
+        assert_eq!(std::fs::read_to_string(&filepath).unwrap(), "b");
+        let num_files = std::fs::read_dir(filepath.parent().unwrap())
+            .unwrap()
+            .count();
+        assert_eq!(num_files, 1);
     }
-    std::fs::write(get_bench_file(&result.bench_id), out).unwrap();
 }